@@ -1,21 +1,25 @@
-use criterion::{black_box, criterion_group, criterion_main, Criterion};
-use offchain_labs::{Config, OffchainLabs};
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+use offchain_labs::config::{DEV_CHAIN_ID, SequencerConfig};
+use offchain_labs::sequencer::{Sequencer, Transaction, TransactionSigner};
+use offchain_labs::zk_rollup::{Account, State};
 
 fn benchmark_transaction_processing(c: &mut Criterion) {
-    let config = Config {
-        zk_params_path: "bench_params.json".to_string(),
-        state_db_path: "bench_state.db".to_string(),
-    };
-
-    let mut hvm = OffchainLabs::new(config).unwrap();
-    let transaction = vec![1, 2, 3, 4];
+    let sender = TransactionSigner::sr25519_from_seed(&[1u8; 32]).unwrap();
+    let recipient = TransactionSigner::sr25519_from_seed(&[2u8; 32]).unwrap();
+    let mut state = State::new();
+    state.set_account(&sender.address(), Account::new(1_000, 0));
+    let transaction = Transaction::transfer(DEV_CHAIN_ID, sender.address(), recipient.address(), 1, 0).sign(&sender);
 
     c.bench_function("process transaction", |b| {
-        b.iter(|| {
-            hvm.process_transaction(black_box(&transaction)).unwrap();
-        })
+        b.iter_batched(
+            || Sequencer::new(state.clone(), SequencerConfig::default()),
+            |mut sequencer| {
+                sequencer.process_transaction(black_box(transaction.clone())).unwrap();
+            },
+            BatchSize::SmallInput,
+        )
     });
 }
 
 criterion_group!(benches, benchmark_transaction_processing);
-criterion_main!(benches);
\ No newline at end of file
+criterion_main!(benches);
@@ -1,16 +1,19 @@
 use crate::error::HVMError;
+use crate::zk_rollup::encoding;
 use ark_bn254::Fr;
 use ark_ff::PrimeField;
-use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
-use ark_relations::lc;
+use ark_serialize::CanonicalSerialize;
 use serde::{Serialize, Deserialize};
 use sha2::{Sha256, Digest};
-use wasmer::{Store, Module, Instance, Value, imports, Memory};
+use wasmer::{imports, CompilerConfig, Cranelift, ExternType, Instance, Memory, Module, Store, Type, Value};
+use wasmer::wasmparser::Operator;
+use wasmer_middlewares::metering::{get_remaining_points, Metering, MeteringPoints};
 use log::{error, debug};
+use std::sync::Arc;
 
 pub mod storage;
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct BendProgram {
     pub id: String,
     pub bytecode: Vec<u8>,
@@ -18,7 +21,7 @@ pub struct BendProgram {
     pub author: String,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ProgramMetadata {
     pub name: String,
     pub version: String,
@@ -35,8 +38,32 @@ impl BendProgram {
         &self.id
     }
 
-    pub fn execute(&self, inputs: Vec<u8>) -> Result<Vec<Fr>, HVMError> {
-        let mut store = Store::default();
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.bytecode.len() + 64);
+        encoding::put_bytes(&mut bytes, &self.bytecode);
+        encoding::put_bytes(&mut bytes, self.metadata.name.as_bytes());
+        encoding::put_bytes(&mut bytes, self.metadata.version.as_bytes());
+        encoding::put_bytes(&mut bytes, self.metadata.description.as_bytes());
+        encoding::put_bytes(&mut bytes, self.author.as_bytes());
+        bytes
+    }
+
+    pub fn check_interface(&self) -> Result<(), HVMError> {
+        let store = Store::default();
+        let module = Module::new(&store, &self.bytecode)
+            .map_err(|e| HVMError::Execution(format!("Failed to create module: {}", e)))?;
+        let export = |name: &str| module.exports().find(|export| export.name() == name).map(|export| export.ty().clone());
+        if !matches!(export("memory"), Some(ExternType::Memory(_))) {
+            return Err(HVMError::Execution(format!("Program {} does not export memory", self.id)));
+        }
+        match export("run") {
+            Some(ExternType::Function(run)) if run.params().is_empty() && run.results() == [Type::I32, Type::I32] => Ok(()),
+            _ => Err(HVMError::Execution(format!("Program {} does not export run: () -> (i32, i32)", self.id))),
+        }
+    }
+
+    pub fn execute(&self, inputs: Vec<u8>, gas_limit: u64) -> Result<Vec<Fr>, HVMError> {
+        let mut store = metered_store(gas_limit);
         let module = Module::new(&store, &self.bytecode)
             .map_err(|e| HVMError::Execution(format!("Failed to create module: {}", e)))?;
         let import_object = imports! {};
@@ -46,7 +73,7 @@ impl BendProgram {
         let memory = instance.exports.get_memory("memory")
             .map_err(|e| HVMError::Execution(format!("Module does not export memory: {}", e)))?;
     
-        self.write_inputs_to_memory(&store, &memory, &inputs)?;
+        self.write_inputs_to_memory(&store, memory, &inputs)?;
     
         let run = instance.exports.get_function("run")
             .map_err(|e| HVMError::Execution(format!("Failed to get run function: {}", e)))?;
@@ -56,8 +83,12 @@ impl BendProgram {
         match result {
             Ok(output) => {
                 debug!("WebAssembly execution successful");
-                self.read_outputs_from_memory(&store, &memory, &output)
+                self.read_outputs_from_memory(&store, memory, &output)
             },
+            Err(_) if get_remaining_points(&mut store, &instance) == MeteringPoints::Exhausted => {
+                error!("WebAssembly execution ran out of gas");
+                Err(HVMError::Execution(format!("Program exceeded its gas limit of {}", gas_limit)))
+            }
             Err(e) => {
                 error!("WebAssembly execution failed: {}", e);
                 Err(HVMError::Execution(format!("Failed to execute program: {}", e)))
@@ -73,15 +104,25 @@ impl BendProgram {
     }
 
     fn read_outputs_from_memory(&self, store: &Store, memory: &Memory, results: &[Value]) -> Result<Vec<Fr>, HVMError> {
-        let ptr = results[0].i32().unwrap() as u64;
-        let len = results[1].i32().unwrap() as u64;
+        let (ptr, len) = match results {
+            [Value::I32(ptr), Value::I32(len)] => (*ptr as u32 as u64, *len as u32 as u64),
+            _ => return Err(HVMError::Execution("Program run must return an (i32, i32) pointer and length".to_string())),
+        };
         let mem_view = memory.view(store);
+        if ptr + len > mem_view.data_size() {
+            return Err(HVMError::Execution(format!(
+                "Program output of {} bytes at {} exceeds the {} byte memory",
+                len,
+                ptr,
+                mem_view.data_size()
+            )));
+        }
         let mut output_bytes = vec![0u8; len as usize];
         mem_view.read(ptr, &mut output_bytes)
             .map_err(|e| HVMError::Execution(format!("Failed to read outputs from memory: {}", e)))?;
     
         let field_elements = output_bytes.chunks_exact(32)
-            .map(|chunk| Fr::from_le_bytes_mod_order(chunk))
+            .map(Fr::from_le_bytes_mod_order)
             .collect::<Vec<_>>();
     
         Ok(field_elements)
@@ -98,28 +139,20 @@ impl BendProgram {
     }
 }
 
-pub struct BendCircuit {
-    pub inputs: Vec<Fr>,
-    pub outputs: Vec<Fr>,
-}
-
-impl Default for BendCircuit {
-    fn default() -> Self {
-        Self {
-            inputs: Vec::new(),
-            outputs: Vec::new(),
-        }
-    }
+pub fn metered_store(gas_limit: u64) -> Store {
+    let mut compiler = Cranelift::default();
+    compiler.push_middleware(Arc::new(Metering::new(gas_limit, |_: &Operator| 1)));
+    Store::new(compiler)
 }
 
-impl ConstraintSynthesizer<Fr> for BendCircuit {
-    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
-        let a = cs.new_witness_variable(|| Ok(Fr::from(10u64)))?;
-        let b = cs.new_witness_variable(|| Ok(Fr::from(20u64)))?;
-        let c = cs.new_input_variable(|| Ok(Fr::from(30u64)))?;
-
-        cs.enforce_constraint(lc!() + a, lc!() + b, lc!() + c)?;
-
-        Ok(())
-    }
+pub fn encode_outputs(outputs: &[Fr]) -> Vec<u8> {
+    outputs
+        .iter()
+        .flat_map(|fr| {
+            let mut bytes = Vec::new();
+            fr.serialize_uncompressed(&mut bytes)
+                .unwrap_or_else(|_| bytes.clear());
+            bytes
+        })
+        .collect()
 }
\ No newline at end of file
@@ -1,12 +1,19 @@
 use crate::error::HVMError;
 use crate::bend::BendProgram;
 use crate::sequencer::Batch;
+use crate::zk_rollup::merkle::Hash;
 use std::collections::HashMap;
 use std::sync::{Arc, RwLock};
 
 pub struct Storage {
     programs: Arc<RwLock<HashMap<String, BendProgram>>>,
-    batches: Arc<RwLock<HashMap<u64, Batch>>>,
+    batches: Arc<RwLock<HashMap<Hash, Batch>>>,
+}
+
+impl Default for Storage {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Storage {
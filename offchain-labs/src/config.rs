@@ -3,6 +3,9 @@ use std::fs::File;
 use std::io::Read;
 use std::path::PathBuf;
 use crate::error::HVMError;
+use crate::sequencer::BatchWeight;
+
+pub const DEV_CHAIN_ID: u64 = 1337;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Config {
@@ -25,12 +28,56 @@ pub struct VerifierConfig {
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default = "SequencerConfig::without_chain_id")]
 pub struct SequencerConfig {
+    pub chain_id: u64,
     pub max_pending_transactions: usize,
     pub max_pending_programs: usize,
     pub batch_interval_seconds: u64,
     pub max_batch_size: usize,
     pub max_programs_per_batch: usize,
+    pub max_retained_snapshots: usize,
+    pub base_fee: u128,
+    pub fee_recipient: String,
+    pub max_transaction_bytes: usize,
+    pub max_batch_bytes: usize,
+    pub max_batch_gas: u64,
+    pub proving_constraints_per_second: u64,
+    pub proving_failure_policy: ProvingFailurePolicy,
+    pub forced_inclusion_window: u64,
+}
+
+impl SequencerConfig {
+    fn without_chain_id() -> Self {
+        Self { chain_id: 0, ..Self::default() }
+    }
+
+    pub fn validate(&self) -> Result<(), HVMError> {
+        if self.chain_id == 0 {
+            return Err(HVMError::Config("chain_id must be set to a nonzero chain id".to_string()));
+        }
+        if self.max_batch_size == 0 {
+            return Err(HVMError::Config("max_batch_size must be at least 1".to_string()));
+        }
+        if self.fee_recipient.is_empty() {
+            return Err(HVMError::Config("fee_recipient must name an account".to_string()));
+        }
+        let empty_batch = BatchWeight::empty(self).bytes;
+        if self.max_batch_bytes < empty_batch {
+            return Err(HVMError::Config(format!(
+                "max_batch_bytes {} is below the {} bytes of an empty batch",
+                self.max_batch_bytes, empty_batch
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ProvingFailurePolicy {
+    #[default]
+    Requeue,
+    Drop,
 }
 
 impl Config {
@@ -38,7 +85,20 @@ impl Config {
         let mut file = File::open("config.json").map_err(|e| HVMError::Config(format!("Failed to open config file: {}", e)))?;
         let mut contents = String::new();
         file.read_to_string(&mut contents).map_err(|e| HVMError::Config(format!("Failed to read config file: {}", e)))?;
-        serde_json::from_str(&contents).map_err(|e| HVMError::Config(format!("Failed to parse config file: {}", e)))
+        let config: Self = serde_json::from_str(&contents).map_err(|e| HVMError::Config(format!("Failed to parse config file: {}", e)))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn validate(&self) -> Result<(), HVMError> {
+        if self.sequencer_config.max_batch_size != self.prover_config.max_batch_size {
+            return Err(HVMError::Config(format!(
+                "Sequencer max_batch_size {} does not match the prover circuit, which is sized for {} transactions",
+                self.sequencer_config.max_batch_size,
+                self.prover_config.max_batch_size
+            )));
+        }
+        self.sequencer_config.validate()
     }
 
     pub fn save(&self) -> Result<(), HVMError> {
@@ -54,20 +114,48 @@ impl Default for Config {
         Self {
             zk_params_path: PathBuf::from("zk_params.json"),
             state_db_path: PathBuf::from("state.db"),
-            prover_config: ProverConfig {
-                proving_key_path: PathBuf::from("proving_key.bin"),
-                max_batch_size: 100,
-            },
-            verifier_config: VerifierConfig {
-                verification_key_path: PathBuf::from("verification_key.bin"),
-            },
-            sequencer_config: SequencerConfig {
-                max_pending_transactions: 1000,
-                max_pending_programs: 100,
-                batch_interval_seconds: 60,
-                max_batch_size: 100,
-                max_programs_per_batch: 10,
-            },
+            prover_config: ProverConfig::default(),
+            verifier_config: VerifierConfig::default(),
+            sequencer_config: SequencerConfig::default(),
+        }
+    }
+}
+
+impl Default for ProverConfig {
+    fn default() -> Self {
+        Self {
+            proving_key_path: PathBuf::from("proving_key.bin"),
+            max_batch_size: 100,
+        }
+    }
+}
+
+impl Default for VerifierConfig {
+    fn default() -> Self {
+        Self {
+            verification_key_path: PathBuf::from("verification_key.bin"),
+        }
+    }
+}
+
+impl Default for SequencerConfig {
+    fn default() -> Self {
+        Self {
+            chain_id: DEV_CHAIN_ID,
+            max_pending_transactions: 1000,
+            max_pending_programs: 100,
+            batch_interval_seconds: 60,
+            max_batch_size: 100,
+            max_programs_per_batch: 10,
+            max_retained_snapshots: 64,
+            base_fee: 0,
+            fee_recipient: "sequencer".to_string(),
+            max_transaction_bytes: 4096,
+            max_batch_bytes: 131_072,
+            max_batch_gas: 30_000_000,
+            proving_constraints_per_second: 250_000,
+            proving_failure_policy: ProvingFailurePolicy::Requeue,
+            forced_inclusion_window: 16,
         }
     }
 }
\ No newline at end of file
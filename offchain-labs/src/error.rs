@@ -1,3 +1,4 @@
+use crate::sequencer::RejectionReason;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -41,6 +42,12 @@ pub enum HVMError {
     #[error("Execution error: {0}")]
     Execution(String),
 
+    #[error("Invalid signature: {0}")]
+    InvalidSignature(String),
+
+    #[error("Transaction rejected: {0}")]
+    Rejected(#[from] RejectionReason),
+
     #[error("Balance error")]
     InsufficientBalance(),
 }
\ No newline at end of file
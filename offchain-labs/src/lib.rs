@@ -7,61 +7,146 @@ pub mod zk_rollup;
 pub mod bend;
 
 pub use config::Config;
-use std::collections::HashMap;
 use error::HVMError;
-use sequencer::Transaction;
-use prover::ZKProver;
-use verifier::ZKVerifier;
+use sequencer::{
+    Batch, BatchHeader, DeploymentReceipt, DepositId, DepositSource, ForcedInclusionSource, L1Deposit, Transaction, TransactionReceipt, TransactionSigner,
+    TransactionStatus,
+};
+use zk_rollup::{RollupEngine, ZKRollup};
 use bend::{BendProgram, storage::Storage};
 
-use ark_bn254::Bn254;
-use ark_groth16::{Groth16, ProvingKey, VerifyingKey};
-use ark_snark::SNARK;
-
-pub struct OffchainLabs {
-    prover: ZKProver,
+pub struct OffchainLabs<R: ZKRollup = RollupEngine> {
+    rollup: R,
     sequencer: sequencer::Sequencer,
-    verifier: ZKVerifier,
     storage: Storage,
-    user_balances: HashMap<String, u64>,
 }
 
 impl OffchainLabs {
     pub fn new(config: Config) -> Result<Self, HVMError> {        
-        let (pk, vk) = Self::generate_zk_keys(&config)?;
-        
-        let prover = ZKProver::new(pk);
+        config.validate()?;
+        let rollup = RollupEngine::setup(&config)?;
+        Self::with_rollup(config, rollup)
+    }
+
+    pub fn optimize_program(&self, program: &BendProgram) -> Result<BendProgram, HVMError> {
+        self.rollup.prover().optimize_program(program)
+    }
+}
+
+impl<R: ZKRollup> OffchainLabs<R> {
+    pub fn with_rollup(config: Config, rollup: R) -> Result<Self, HVMError> {
+        config.validate()?;
         let sequencer = sequencer::Sequencer::new(zk_rollup::State::default(), config.sequencer_config.clone());
-        let verifier = ZKVerifier::new(vk);
         let storage = Storage::new();
-        let user_balances = HashMap::new();
 
         Ok(Self {
-            prover,
+            rollup,
             sequencer,
-            verifier,
             storage,
-            user_balances,
         })
     }
 
+    pub fn rollup(&self) -> &R {
+        &self.rollup
+    }
+
     pub fn process_transaction(&mut self, transaction: Transaction) -> Result<bool, HVMError> {
         self.sequencer.process_transaction(transaction)?;
-    
+        self.process_pending_batch()
+    }
+
+    pub fn process_pending_batch(&mut self) -> Result<bool, HVMError> {
         if let Some(batch) = self.sequencer.create_batch(true)? {
-            let proof = self.prover.generate_proof(&batch)?;
-            let is_valid = self.verifier.verify_proof(&proof, &batch.programs().iter().flat_map(|p| p.get_public_inputs()).collect::<Vec<_>>())?;
-            
-            if is_valid {
-                self.sequencer.apply_proof(proof, &batch)?;
-            }
-    
-            Ok(is_valid)
+            let result = self.prove_and_commit(&batch);
+            self.handle_proving_result(&batch, &result);
+            result
         } else {
             Ok(true)
         }
     }
 
+    pub fn recover(&mut self, wal_path: impl AsRef<std::path::Path>) -> Result<usize, HVMError> {
+        let backlog = self.sequencer.open_wal(wal_path)?;
+        for program in self.sequencer.deployed_programs().values() {
+            self.rollup.register_program(program.clone());
+        }
+        let restored = backlog.len();
+        for sealed in backlog {
+            let result = self.prove_and_commit(&sealed.batch);
+            self.handle_proving_result(&sealed.batch, &result);
+            if !matches!(result, Ok(true)) {
+                break;
+            }
+        }
+        Ok(restored)
+    }
+
+    fn handle_proving_result(&mut self, batch: &Batch, result: &Result<bool, HVMError>) {
+        match result {
+            Ok(true) => {}
+            Ok(false) => self.sequencer.handle_proving_failure(&batch.batch_id(), "Proof failed verification"),
+            Err(e) => self.sequencer.handle_proving_failure(&batch.batch_id(), &e.to_string()),
+        }
+    }
+
+    fn prove_and_commit(&mut self, batch: &Batch) -> Result<bool, HVMError> {
+        let mut state = self.sequencer.get_current_state();
+        let proof = self.rollup.generate_proof(&state, batch)?;
+        let is_valid = self.rollup.verify_proof(&proof)?;
+
+        if is_valid {
+            let outcomes = self.rollup.apply_proof(&mut state, &proof, batch)?;
+            self.sequencer.commit_batch(state, batch, &outcomes)?;
+            for program in zk_rollup::transition::collect_deployments(batch, &outcomes) {
+                self.rollup.register_program(program.clone());
+            }
+        }
+
+        Ok(is_valid)
+    }
+
+    pub fn withdraw(&mut self, signer: &TransactionSigner, l1_recipient: &str, amount: u128, nonce: u64) -> Result<bool, HVMError> {
+        let chain_id = self.sequencer.config().chain_id;
+        let transaction = Transaction::withdrawal(chain_id, signer.address(), l1_recipient.to_string(), amount, nonce).sign(signer);
+        self.process_transaction(transaction)
+    }
+
+    pub fn batch_header(&self, batch_id: &zk_rollup::merkle::Hash) -> Option<&BatchHeader> {
+        self.sequencer.batch_header(batch_id)
+    }
+
+    pub fn transaction_receipt(&self, hash: &zk_rollup::merkle::Hash) -> Option<&TransactionReceipt> {
+        self.sequencer.receipt(hash)
+    }
+
+    pub fn batch_receipts(&self, batch_id: &zk_rollup::merkle::Hash) -> Vec<&TransactionReceipt> {
+        self.sequencer.batch_receipts(batch_id)
+    }
+
+    pub fn deployment_receipt(&self, program_id: &str) -> Option<&DeploymentReceipt> {
+        self.sequencer.deployment_receipt(program_id)
+    }
+
+    pub fn mark_batch_submitted(&mut self, batch_id: &zk_rollup::merkle::Hash) -> Result<(), HVMError> {
+        self.sequencer.mark_batch_submitted(batch_id)
+    }
+
+    pub fn mark_batch_finalized(&mut self, batch_id: &zk_rollup::merkle::Hash) -> Result<(), HVMError> {
+        self.sequencer.mark_batch_finalized(batch_id)
+    }
+
+    pub fn get_withdrawals(&self, batch_id: &zk_rollup::merkle::Hash) -> &[zk_rollup::Withdrawal] {
+        self.sequencer.withdrawals(batch_id)
+    }
+
+    pub fn get_rejected_deposits(&self, batch_id: &zk_rollup::merkle::Hash) -> &[DepositId] {
+        self.sequencer.rejected_deposits(batch_id)
+    }
+
+    pub fn generate_withdrawal_claim(&self, batch_id: &zk_rollup::merkle::Hash, index: u32) -> Result<zk_rollup::WithdrawalClaim, HVMError> {
+        self.sequencer.generate_withdrawal_claim(batch_id, index)
+    }
+
     pub fn submit_program(&mut self, program: BendProgram) -> Result<(), HVMError> {
         self.sequencer.submit_program(program.clone())?;
         self.storage.store_program(program)
@@ -69,68 +154,104 @@ impl OffchainLabs {
 
     pub fn deploy_program(&mut self, program: BendProgram) -> Result<(), HVMError> {
         self.sequencer.deploy_program(program.clone())?;
+        self.rollup.register_program(program.clone());
         self.storage.store_program(program)
     }
 
-    pub fn execute_program(&mut self, program_id: &str, inputs: Vec<u8>, user_id: &str) -> Result<Vec<u8>, HVMError> {
+    pub fn execute_program(&mut self, program_id: &str, inputs: Vec<u8>, signer: &TransactionSigner, nonce: u64) -> Result<Vec<u8>, HVMError> {
         let program = self.storage.load_program(program_id)?;
-        let estimated_usage = self.prover.estimate_resource_usage(&program)?;
+        let estimated_usage = self.rollup.estimate_resource_usage(&program)?;
         let required_balance = self.calculate_required_balance(&estimated_usage);
 
-        self.check_and_deduct_balance(user_id, required_balance)?;
+        if self.get_balance(&signer.address()) < required_balance {
+            return Err(HVMError::InsufficientBalance());
+        }
 
-        let result = self.sequencer.execute_program(program_id, inputs)?;
+        let config = self.sequencer.config();
+        let payment = Transaction::transfer(config.chain_id, signer.address(), config.fee_recipient.clone(), required_balance, nonce).sign(signer);
+        let payment_hash = payment.hash();
+        if !self.process_transaction(payment)? {
+            return Err(HVMError::Execution(format!("Payment for {} failed verification", program_id)));
+        }
+        match self.sequencer.receipt(&payment_hash).map(|receipt| &receipt.status) {
+            Some(status) if status.is_proven() => {}
+            Some(TransactionStatus::Failed(reason)) => {
+                return Err(HVMError::Execution(format!("Payment for {} failed: {}", program_id, reason)));
+            }
+            _ => return Err(HVMError::Execution(format!("Payment for {} is not included in a proven batch", program_id))),
+        }
 
-        let actual_usage = self.prover.estimate_resource_usage(&program)?;
-        let actual_cost = self.calculate_actual_cost(&actual_usage);
-        self.refund_excess_balance(user_id, required_balance, actual_cost);
+        self.sequencer.execute_program(program_id, inputs)
+    }
 
-        Ok(result)
+    fn calculate_required_balance(&self, usage: &prover::ResourceUsage) -> u128 {
+        usage.cpu_cycles as u128 + usage.memory_usage as u128
     }
 
-    fn calculate_required_balance(&self, usage: &prover::ResourceUsage) -> u64 {
-        usage.cpu_cycles + usage.memory_usage
+    pub fn deposit_funds(&mut self, deposit: L1Deposit) -> bool {
+        self.sequencer.submit_deposit(deposit)
     }
 
-    fn check_and_deduct_balance(&mut self, user_id: &str, amount: u64) -> Result<(), HVMError> {
-        let balance = self.user_balances.entry(user_id.to_string()).or_insert(0);
-        if *balance < amount {
-            return Err(HVMError::InsufficientBalance());
-        }
-        *balance -= amount;
-        Ok(())
+    pub fn ingest_deposits<S: DepositSource>(&mut self, source: &mut S) -> Result<usize, HVMError> {
+        self.sequencer.ingest_deposits(source)
     }
 
-    fn calculate_actual_cost(&self, usage: &prover::ResourceUsage) -> u64 {
-        usage.cpu_cycles + usage.memory_usage
+    pub fn pending_deposits_count(&self) -> usize {
+        self.sequencer.pending_deposits_count()
     }
 
-    fn refund_excess_balance(&mut self, user_id: &str, deducted: u64, actual_cost: u64) {
-        if actual_cost < deducted {
-            let refund = deducted - actual_cost;
-            *self.user_balances.entry(user_id.to_string()).or_insert(0) += refund;
-        }
+    pub fn ingest_forced_transactions<S: ForcedInclusionSource>(&mut self, source: &mut S) -> Result<usize, HVMError> {
+        self.sequencer.ingest_forced(source)
+    }
+
+    pub fn forced_transactions_count(&self) -> usize {
+        self.sequencer.forced_transactions_count()
+    }
+
+    pub fn get_balance(&self, user_id: &str) -> u128 {
+        self.sequencer.state().get_account(user_id).map_or(0, |account| account.balance)
     }
 
-    pub fn deposit_funds(&mut self, user_id: &str, amount: u64) {
-        *self.user_balances.entry(user_id.to_string()).or_insert(0) += amount;
+    pub fn prove_account(&self, user_id: &str) -> zk_rollup::AccountProof {
+        self.sequencer.state().prove_account(user_id)
     }
 
-    pub fn get_balance(&self, user_id: &str) -> u64 {
-        *self.user_balances.get(user_id).unwrap_or(&0)
+    pub fn state_root(&self) -> zk_rollup::merkle::Hash {
+        self.sequencer.state().root()
     }
 
-    fn generate_zk_keys(_config: &Config) -> Result<(ProvingKey<Bn254>, VerifyingKey<Bn254>), HVMError> {
-        let circuit = bend::BendCircuit::default();
-        let mut rng = ark_std::rand::thread_rng();
-        Groth16::<Bn254>::circuit_specific_setup(circuit, &mut rng)
-            .map_err(|e| HVMError::Setup(format!("Failed to generate ZK-SNARK keys: {}", e)))
+    pub fn rollback_to_batch(&mut self, batch_id: &zk_rollup::merkle::Hash) -> Result<Vec<zk_rollup::merkle::Hash>, HVMError> {
+        self.revert_with(|sequencer| sequencer.rollback_to_batch(batch_id))
+    }
+
+    pub fn revert_batch(&mut self, batch_id: &zk_rollup::merkle::Hash) -> Result<Vec<zk_rollup::merkle::Hash>, HVMError> {
+        self.revert_with(|sequencer| sequencer.revert_batch(batch_id))
+    }
+
+    fn revert_with<F>(&mut self, revert: F) -> Result<Vec<zk_rollup::merkle::Hash>, HVMError>
+    where
+        F: FnOnce(&mut sequencer::Sequencer) -> Result<Vec<zk_rollup::merkle::Hash>, HVMError>,
+    {
+        let deployed: Vec<String> = self.sequencer.deployed_programs().keys().cloned().collect();
+        let reverted = revert(&mut self.sequencer)?;
+        for program_id in deployed.iter().filter(|program_id| !self.sequencer.deployed_programs().contains_key(*program_id)) {
+            self.rollup.unregister_program(program_id);
+        }
+        Ok(reverted)
+    }
+
+    pub fn state_diff(&self, batch_id: &zk_rollup::merkle::Hash) -> Option<&zk_rollup::StateDiff> {
+        self.sequencer.state_diff(batch_id)
     }
 
     pub fn get_current_state(&self) -> Result<zk_rollup::State, HVMError> {
         Ok(self.sequencer.get_current_state())
     }
 
+    pub fn collected_fees(&self) -> u128 {
+        self.sequencer.collected_fees()
+    }
+
     pub fn pending_transactions_count(&self) -> usize {
         self.sequencer.pending_transactions_count()
     }
@@ -139,7 +260,7 @@ impl OffchainLabs {
         self.sequencer.processed_transactions_count()
     }
 
-    pub fn get_pending_transactions(&self) -> &std::collections::VecDeque<Transaction> {
+    pub fn get_pending_transactions(&self) -> Vec<&Transaction> {
         self.sequencer.get_pending_transactions()
     }
 
@@ -148,10 +269,6 @@ impl OffchainLabs {
     }
 
     pub fn estimate_program_resources(&self, program: &BendProgram) -> Result<prover::ResourceUsage, HVMError> {
-        self.prover.estimate_resource_usage(program)
-    }
-
-    pub fn optimize_program(&self, program: &BendProgram) -> Result<BendProgram, HVMError> {
-        self.prover.optimize_program(program)
+        self.rollup.estimate_resource_usage(program)
     }
 }
\ No newline at end of file
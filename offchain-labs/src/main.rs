@@ -1,4 +1,4 @@
-use offchain_labs::{Config, OffchainLabs, sequencer::Transaction};
+use offchain_labs::{Config, OffchainLabs, sequencer::{L1Deposit, Transaction, TransactionSigner}};
 use log::{info, error};
 
 #[tokio::main]
@@ -10,14 +10,22 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         Config::default()
     });
     
+    let chain_id = config.sequencer_config.chain_id;
     let mut hvm = OffchainLabs::new(config)?;
 
     info!("OffchainLabs initialized");
 
-    let transactions = vec![
-        Transaction::new("Alice".to_string(), "Bob".to_string(), vec![100], 1, "test_program".to_string()),
-        Transaction::new("Bob".to_string(), "Charlie".to_string(), vec![50], 1, "test_program".to_string()),
-        Transaction::new("Charlie".to_string(), "Alice".to_string(), vec![25], 1, "test_program".to_string()),
+    let alice = TransactionSigner::sr25519_from_seed(&[1u8; 32])?;
+    let bob = TransactionSigner::sr25519_from_seed(&[2u8; 32])?;
+    let charlie = TransactionSigner::sr25519_from_seed(&[3u8; 32])?;
+
+    hvm.deposit_funds(L1Deposit::new(1, 0, alice.address(), 100));
+    hvm.process_pending_batch()?;
+
+    let transactions = [
+        Transaction::transfer(chain_id, alice.address(), bob.address(), 100, 0).sign(&alice),
+        Transaction::transfer(chain_id, bob.address(), charlie.address(), 50, 0).sign(&bob),
+        Transaction::transfer(chain_id, charlie.address(), alice.address(), 25, 0).sign(&charlie),
     ];
 
     for (i, tx) in transactions.iter().enumerate() {
@@ -1,45 +1,40 @@
 use crate::error::HVMError;
-use crate::zk_rollup::Proof;
+use crate::zk_rollup::{transition, Proof, ProofSystem, State};
+use crate::zk_rollup::merkle::Hash;
+use crate::zk_rollup::proof::verifying_key_hash;
+use crate::zk_rollup::circuit::StateTransitionCircuit;
 use crate::sequencer::Batch;
-use crate::Transaction;
-use crate::bend::{BendProgram, BendCircuit};
+use crate::bend::{self, BendProgram};
 use ark_bn254::Bn254;
 use ark_groth16::{Groth16, ProvingKey};
 use ark_snark::SNARK;
 use ark_serialize::CanonicalSerialize;
 use ark_std::rand::thread_rng;
-use wasmer::{Store, Module, Instance, Cranelift};
+use wasmer::{Module, Instance};
 use std::time::Instant;
 use std::collections::HashMap;
 
 pub struct ZKProver {
     proving_key: ProvingKey<Bn254>,
+    vk_hash: Hash,
+    capacity: usize,
     program_cache: HashMap<String, BendProgram>,
 }
 
 impl ZKProver {
-    pub fn new(proving_key: ProvingKey<Bn254>) -> Self {
-        Self {
+    pub fn new(proving_key: ProvingKey<Bn254>, capacity: usize) -> Result<Self, HVMError> {
+        Ok(Self {
+            vk_hash: verifying_key_hash(&proving_key.vk)?,
             proving_key,
+            capacity,
             program_cache: HashMap::new(),
-        }
+        })
     }
 
-    pub fn generate_proof(&self, batch: &Batch) -> Result<Proof, HVMError> {
-        let mut inputs = Vec::new();
-        let mut outputs = Vec::new();
-
-        for transaction in batch.transactions() {
-            let program = self.get_program_for_transaction(transaction)?;
-            let execution_result = program.execute(transaction.amount.clone())?;
-            inputs.extend(program.get_public_inputs());
-            outputs.extend(execution_result);
-        }
-
-        let circuit = BendCircuit {
-            inputs,
-            outputs,
-        };
+    pub fn generate_proof(&self, state: &State, batch: &Batch) -> Result<Proof, HVMError> {
+        let transition = transition::apply_batch(state, batch, &self.program_cache)?;
+        let circuit = StateTransitionCircuit::new(self.capacity, &transition, batch.batch_id())?;
+        let public_inputs = circuit.public_inputs();
         let mut rng = thread_rng();
         
         let proof = Groth16::<Bn254>::prove(&self.proving_key, circuit, &mut rng)
@@ -49,20 +44,26 @@ impl ZKProver {
         proof.serialize_uncompressed(&mut proof_bytes)
             .map_err(|e| HVMError::Prover(format!("Failed to serialize proof: {}", e)))?;
         
-        Ok(Proof::new(proof_bytes))
+        Ok(Proof::new(
+            ProofSystem::Groth16Bn254,
+            self.vk_hash,
+            &public_inputs,
+            batch.batch_id(),
+            proof_bytes,
+        ))
     }
 
-    fn get_program_for_transaction(&self, transaction: &Transaction) -> Result<&BendProgram, HVMError> {
-        let program_id = &transaction.program_id;
-        println!("Program Id: {:?}", program_id);
+    pub fn vk_hash(&self) -> &Hash {
+        &self.vk_hash
+    }
 
-        self.program_cache.get(program_id)
-            .ok_or_else(|| HVMError::Prover(format!("Program not found for ID: {}", program_id)))
+    pub fn capacity(&self) -> usize {
+        self.capacity
     }
 
     pub fn estimate_resource_usage(&self, program: &BendProgram) -> Result<ResourceUsage, HVMError> {
-        let mut store = Store::new(Cranelift::default());
-        let module = Module::new(&mut store, &program.bytecode)
+        let mut store = bend::metered_store(transition::PROGRAM_GAS_LIMIT);
+        let module = Module::new(&store, &program.bytecode)
             .map_err(|e| HVMError::Estimation(format!("Failed to create module: {}", e)))?;
         let import_object = wasmer::imports! {};
         let instance = Instance::new(&mut store, &module, &import_object)
@@ -74,14 +75,14 @@ impl ZKProver {
         let run = instance.exports.get_function("run")
             .map_err(|e| HVMError::Estimation(format!("Failed to get run function: {}", e)))?;
 
-        let start_memory = memory.view(&store).data_size() as u64;
+        let start_memory = memory.view(&store).data_size();
         let start_time = Instant::now();
 
         run.call(&mut store, &[])
             .map_err(|e| HVMError::Estimation(format!("Failed to execute program: {}", e)))?;
 
         let end_time = Instant::now();
-        let end_memory = memory.view(&store).data_size() as u64;
+        let end_memory = memory.view(&store).data_size();
 
         Ok(ResourceUsage {
             cpu_cycles: end_time.duration_since(start_time).as_micros() as u64,
@@ -102,6 +103,14 @@ impl ZKProver {
     pub fn add_program(&mut self, program: BendProgram) {
         self.program_cache.insert(program.id().to_string(), program);
     }
+
+    pub fn remove_program(&mut self, program_id: &str) {
+        self.program_cache.remove(program_id);
+    }
+
+    pub fn programs(&self) -> &HashMap<String, BendProgram> {
+        &self.program_cache
+    }
 }
 
 #[derive(Debug)]
@@ -110,6 +119,6 @@ pub struct ResourceUsage {
     pub memory_usage: u64,
 }
 
-pub fn create_zk_prover(proving_key: ProvingKey<Bn254>) -> ZKProver {
-    ZKProver::new(proving_key)
+pub fn create_zk_prover(proving_key: ProvingKey<Bn254>, capacity: usize) -> Result<ZKProver, HVMError> {
+    ZKProver::new(proving_key, capacity)
 }
\ No newline at end of file
@@ -0,0 +1,181 @@
+use super::mempool::Mempool;
+use super::transaction::{Transaction, TransactionKind};
+use crate::bend::BendProgram;
+use crate::zk_rollup::merkle::{self, Hash};
+use crate::zk_rollup::State;
+use std::collections::HashMap;
+use thiserror::Error;
+
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum RejectionReason {
+    #[error("nonce too low: account is at {expected}, got {got}")]
+    NonceTooLow { expected: u64, got: u64 },
+
+    #[error("max fee {max_fee} is below the base fee {base_fee}")]
+    FeeBelowBaseFee { max_fee: u128, base_fee: u128 },
+
+    #[error("insufficient balance: {required} required, {available} available")]
+    InsufficientBalance { required: u128, available: u128 },
+
+    #[error("program not deployed: {0}")]
+    UnknownProgram(String),
+
+    #[error("program already deployed: {0}")]
+    ProgramAlreadyDeployed(String),
+
+    #[error("payload of {size} bytes exceeds the {limit} byte limit")]
+    PayloadTooLarge { size: usize, limit: usize },
+
+    #[error("transaction exceeds the resource limits of an empty batch")]
+    ExceedsBatchLimits,
+
+    #[error("transaction is for chain {got}, expected chain {expected}")]
+    WrongChain { expected: u64, got: u64 },
+
+    #[error("replacement needs a max fee of at least {min_max_fee} and a priority tip of at least {min_priority_tip}")]
+    ReplacementUnderpriced { min_max_fee: u128, min_priority_tip: u128 },
+
+    #[error("duplicate transaction {}", merkle::to_hex(.0))]
+    DuplicateTransaction(Hash),
+
+    #[error("malformed transaction: {0}")]
+    Malformed(String),
+}
+
+pub struct AdmissionContext<'a> {
+    pub state: &'a State,
+    pub deployed_programs: &'a HashMap<String, BendProgram>,
+    pub mempool: &'a Mempool,
+    pub base_fee: u128,
+}
+
+impl AdmissionContext<'_> {
+    pub fn account_nonce(&self, address: &str) -> u64 {
+        self.state.get_account(address).map_or(0, |account| account.nonce)
+    }
+}
+
+pub trait AdmissionValidator: Send + Sync {
+    fn validate(&self, transaction: &Transaction, context: &AdmissionContext) -> Result<(), RejectionReason>;
+}
+
+pub struct NonceValidator;
+
+impl AdmissionValidator for NonceValidator {
+    fn validate(&self, transaction: &Transaction, context: &AdmissionContext) -> Result<(), RejectionReason> {
+        let expected = context.account_nonce(&transaction.sender);
+        if transaction.nonce < expected {
+            return Err(RejectionReason::NonceTooLow { expected, got: transaction.nonce });
+        }
+        Ok(())
+    }
+}
+
+pub struct BalanceValidator;
+
+impl AdmissionValidator for BalanceValidator {
+    fn validate(&self, transaction: &Transaction, context: &AdmissionContext) -> Result<(), RejectionReason> {
+        let fee = transaction.effective_fee(context.base_fee).ok_or(RejectionReason::FeeBelowBaseFee {
+            max_fee: transaction.max_fee,
+            base_fee: context.base_fee,
+        })?;
+        let required = transaction
+            .value()
+            .checked_add(fee)
+            .ok_or_else(|| RejectionReason::Malformed("Amount plus fee overflows".to_string()))?;
+        let available = context.state.get_account(&transaction.sender).map_or(0, |account| account.balance);
+        if available < required {
+            return Err(RejectionReason::InsufficientBalance { required, available });
+        }
+        Ok(())
+    }
+}
+
+pub struct ProgramValidator;
+
+impl AdmissionValidator for ProgramValidator {
+    fn validate(&self, transaction: &Transaction, context: &AdmissionContext) -> Result<(), RejectionReason> {
+        match &transaction.kind {
+            TransactionKind::ProgramCall { program_id, .. } if !context.deployed_programs.contains_key(program_id) => {
+                Err(RejectionReason::UnknownProgram(program_id.clone()))
+            }
+            TransactionKind::DeployProgram(program) if program.bytecode.is_empty() => {
+                Err(RejectionReason::Malformed(format!("Program {} has no bytecode", program.id())))
+            }
+            TransactionKind::DeployProgram(program) if context.deployed_programs.contains_key(program.id()) => {
+                Err(RejectionReason::ProgramAlreadyDeployed(program.id().to_string()))
+            }
+            TransactionKind::DeployProgram(program) => program
+                .check_interface()
+                .map_err(|e| RejectionReason::Malformed(e.to_string())),
+            _ => Ok(()),
+        }
+    }
+}
+
+pub struct PayloadSizeValidator {
+    pub max_bytes: usize,
+}
+
+impl AdmissionValidator for PayloadSizeValidator {
+    fn validate(&self, transaction: &Transaction, _context: &AdmissionContext) -> Result<(), RejectionReason> {
+        let size = transaction.encoded_size();
+        if size > self.max_bytes {
+            return Err(RejectionReason::PayloadTooLarge { size, limit: self.max_bytes });
+        }
+        Ok(())
+    }
+}
+
+pub struct DuplicateValidator;
+
+impl AdmissionValidator for DuplicateValidator {
+    fn validate(&self, transaction: &Transaction, context: &AdmissionContext) -> Result<(), RejectionReason> {
+        let hash = transaction.hash();
+        if context.mempool.contains(&hash) {
+            return Err(RejectionReason::DuplicateTransaction(hash));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+pub struct AdmissionPipeline {
+    validators: Vec<Box<dyn AdmissionValidator>>,
+}
+
+impl AdmissionPipeline {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn standard(max_transaction_bytes: usize) -> Self {
+        Self::new()
+            .with_validator(PayloadSizeValidator { max_bytes: max_transaction_bytes })
+            .with_validator(DuplicateValidator)
+            .with_validator(NonceValidator)
+            .with_validator(BalanceValidator)
+            .with_validator(ProgramValidator)
+    }
+
+    pub fn with_validator<V: AdmissionValidator + 'static>(mut self, validator: V) -> Self {
+        self.push(validator);
+        self
+    }
+
+    pub fn push<V: AdmissionValidator + 'static>(&mut self, validator: V) {
+        self.validators.push(Box::new(validator));
+    }
+
+    pub fn len(&self) -> usize {
+        self.validators.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.validators.is_empty()
+    }
+
+    pub fn validate(&self, transaction: &Transaction, context: &AdmissionContext) -> Result<(), RejectionReason> {
+        self.validators.iter().try_for_each(|validator| validator.validate(transaction, context))
+    }
+}
\ No newline at end of file
@@ -1,32 +1,171 @@
+use super::deposit::{DepositId, L1Deposit};
 use super::transaction::Transaction;
 use crate::bend::BendProgram;
+use crate::zk_rollup::encoding;
+use crate::zk_rollup::merkle::{self, Hash};
 use serde::{Serialize, Deserialize};
-use std::time::{SystemTime, UNIX_EPOCH};
+use sha2::{Sha256, Digest};
+
+const HEADER_DOMAIN: &[u8] = b"hvm-rollup/batch-header/v4";
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BatchHeader {
+    #[serde(with = "hex::serde")]
+    pub parent_hash: Hash,
+    pub height: u64,
+    pub chain_id: u64,
+    #[serde(with = "hex::serde")]
+    pub deposits_root: Hash,
+    #[serde(with = "hex::serde")]
+    pub transactions_root: Hash,
+    #[serde(with = "hex::serde")]
+    pub programs_root: Hash,
+    pub base_fee: u128,
+    pub fee_recipient: String,
+    #[serde(with = "hex::serde")]
+    pub pre_state_root: Hash,
+    #[serde(with = "hex::serde")]
+    pub post_state_root: Hash,
+    #[serde(with = "hex::serde")]
+    pub withdrawals_root: Hash,
+    #[serde(with = "hex::serde")]
+    pub rejected_deposits_root: Hash,
+}
+
+impl BatchHeader {
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(256);
+        bytes.extend_from_slice(&self.parent_hash);
+        bytes.extend_from_slice(&self.height.to_le_bytes());
+        bytes.extend_from_slice(&self.chain_id.to_le_bytes());
+        bytes.extend_from_slice(&self.deposits_root);
+        bytes.extend_from_slice(&self.transactions_root);
+        bytes.extend_from_slice(&self.programs_root);
+        bytes.extend_from_slice(&self.base_fee.to_le_bytes());
+        encoding::put_bytes(&mut bytes, self.fee_recipient.as_bytes());
+        bytes.extend_from_slice(&self.pre_state_root);
+        bytes.extend_from_slice(&self.post_state_root);
+        bytes.extend_from_slice(&self.withdrawals_root);
+        bytes.extend_from_slice(&self.rejected_deposits_root);
+        bytes
+    }
+
+    pub fn hash(&self) -> Hash {
+        let mut hasher = Sha256::new();
+        hasher.update(HEADER_DOMAIN);
+        hasher.update(self.encode());
+        hasher.finalize().into()
+    }
+
+    pub fn extends(&self, parent: &BatchHeader) -> bool {
+        self.parent_hash == parent.hash()
+            && self.height == parent.height + 1
+            && self.chain_id == parent.chain_id
+            && self.pre_state_root == parent.post_state_root
+    }
+}
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Batch {
+    header: BatchHeader,
+    deposits: Vec<L1Deposit>,
     transactions: Vec<Transaction>,
     programs: Vec<BendProgram>,
-    timestamp: u64,
-    batch_id: u64,
+    #[serde(default)]
+    rejected_deposits: Vec<DepositId>,
 }
 
 impl Batch {
     pub fn new(transactions: Vec<Transaction>, programs: Vec<BendProgram>) -> Self {
-        let timestamp = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
-        
-        static BATCH_COUNTER: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
-        let batch_id = BATCH_COUNTER.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        Self::with_deposits(Vec::new(), transactions, programs)
+    }
+
+    pub fn with_deposits(deposits: Vec<L1Deposit>, transactions: Vec<Transaction>, programs: Vec<BendProgram>) -> Self {
+        let header = BatchHeader {
+            deposits_root: merkle::dense_root(&deposits.iter().map(L1Deposit::leaf_hash).collect::<Vec<_>>()),
+            transactions_root: merkle::dense_root(&transactions.iter().map(Transaction::hash).collect::<Vec<_>>()),
+            programs_root: merkle::dense_root(&programs.iter().map(program_leaf).collect::<Vec<_>>()),
+            rejected_deposits_root: merkle::dense_root(&[]),
+            ..BatchHeader::default()
+        };
 
         Self {
+            header,
+            deposits,
             transactions,
             programs,
-            timestamp,
-            batch_id,
+            rejected_deposits: Vec::new(),
+        }
+    }
+
+    pub fn with_parent(mut self, parent: Option<&BatchHeader>) -> Self {
+        match parent {
+            Some(parent) => {
+                self.header.parent_hash = parent.hash();
+                self.header.height = parent.height + 1;
+            }
+            None => {
+                self.header.parent_hash = [0u8; 32];
+                self.header.height = 0;
+            }
         }
+        self
+    }
+
+    pub fn with_chain_id(mut self, chain_id: u64) -> Self {
+        self.header.chain_id = chain_id;
+        self
+    }
+
+    pub fn with_base_fee(mut self, base_fee: u128) -> Self {
+        self.header.base_fee = base_fee;
+        self
+    }
+
+    pub fn with_fee_recipient(mut self, fee_recipient: String) -> Self {
+        self.header.fee_recipient = fee_recipient;
+        self
+    }
+
+    pub fn with_state_roots(mut self, pre_state_root: Hash, post_state_root: Hash) -> Self {
+        self.header.pre_state_root = pre_state_root;
+        self.header.post_state_root = post_state_root;
+        self
+    }
+
+    pub fn with_withdrawals_root(mut self, withdrawals_root: Hash) -> Self {
+        self.header.withdrawals_root = withdrawals_root;
+        self
+    }
+
+    pub fn with_rejected_deposits(mut self, rejected_deposits: Vec<DepositId>) -> Self {
+        self.header.rejected_deposits_root = rejected_deposits_root(&self.deposits, &rejected_deposits).unwrap_or_default();
+        self.rejected_deposits = rejected_deposits;
+        self
+    }
+
+    pub fn header(&self) -> &BatchHeader {
+        &self.header
+    }
+
+    pub fn chain_id(&self) -> u64 {
+        self.header.chain_id
+    }
+
+    pub fn base_fee(&self) -> u128 {
+        self.header.base_fee
+    }
+
+    pub fn fee_recipient(&self) -> &str {
+        &self.header.fee_recipient
+    }
+
+    pub fn deposits(&self) -> &[L1Deposit] {
+        &self.deposits
+    }
+
+    pub fn rejected_deposits(&self) -> &[DepositId] {
+        &self.rejected_deposits
     }
 
     pub fn transactions(&self) -> &[Transaction] {
@@ -37,11 +176,62 @@ impl Batch {
         &self.programs
     }
 
-    pub fn timestamp(&self) -> u64 {
-        self.timestamp
+    pub fn batch_id(&self) -> Hash {
+        self.header.hash()
     }
 
-    pub fn batch_id(&self) -> u64 {
-        self.batch_id
+    pub fn height(&self) -> u64 {
+        self.header.height
     }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = self.header.encode();
+        bytes.extend_from_slice(&(self.deposits.len() as u32).to_le_bytes());
+        for deposit in &self.deposits {
+            bytes.extend_from_slice(&deposit.encode());
+        }
+        bytes.extend_from_slice(&(self.transactions.len() as u32).to_le_bytes());
+        for transaction in &self.transactions {
+            bytes.extend_from_slice(&transaction.encode());
+        }
+        bytes.extend_from_slice(&(self.programs.len() as u32).to_le_bytes());
+        for program in &self.programs {
+            bytes.extend_from_slice(&program.encode());
+        }
+        bytes.extend_from_slice(&(self.rejected_deposits.len() as u32).to_le_bytes());
+        for id in &self.rejected_deposits {
+            bytes.extend_from_slice(&id.block_number.to_le_bytes());
+            bytes.extend_from_slice(&id.event_index.to_le_bytes());
+        }
+        bytes
+    }
+
+    pub fn encoded_size(&self) -> usize {
+        self.encode().len()
+    }
+
+    pub fn verify_contents(&self) -> bool {
+        let expected = Self::with_deposits(self.deposits.clone(), self.transactions.clone(), self.programs.clone());
+        self.header.deposits_root == expected.header.deposits_root
+            && self.header.transactions_root == expected.header.transactions_root
+            && self.header.programs_root == expected.header.programs_root
+            && rejected_deposits_root(&self.deposits, &self.rejected_deposits) == Some(self.header.rejected_deposits_root)
+    }
+}
+
+pub fn verify_chain(batches: &[Batch]) -> bool {
+    batches.iter().all(Batch::verify_contents)
+        && batches.windows(2).all(|pair| pair[1].header().extends(pair[0].header()))
+}
+
+fn rejected_deposits_root(deposits: &[L1Deposit], rejected: &[DepositId]) -> Option<Hash> {
+    let leaves = rejected
+        .iter()
+        .map(|id| deposits.iter().find(|deposit| deposit.id() == *id).map(L1Deposit::leaf_hash))
+        .collect::<Option<Vec<_>>>()?;
+    Some(merkle::dense_root(&leaves))
+}
+
+fn program_leaf(program: &BendProgram) -> Hash {
+    Sha256::digest(program.encode()).into()
 }
\ No newline at end of file
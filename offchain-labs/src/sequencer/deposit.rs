@@ -0,0 +1,138 @@
+use crate::error::HVMError;
+use crate::zk_rollup::encoding;
+use crate::zk_rollup::merkle::Hash;
+use serde::{Serialize, Deserialize};
+use sha2::{Sha256, Digest};
+use std::collections::VecDeque;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct DepositId {
+    pub block_number: u64,
+    pub event_index: u32,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct L1Deposit {
+    pub block_number: u64,
+    pub event_index: u32,
+    pub recipient: String,
+    pub amount: u128,
+}
+
+impl L1Deposit {
+    pub fn new(block_number: u64, event_index: u32, recipient: String, amount: u128) -> Self {
+        Self { block_number, event_index, recipient, amount }
+    }
+
+    pub fn id(&self) -> DepositId {
+        DepositId { block_number: self.block_number, event_index: self.event_index }
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(36 + self.recipient.len());
+        bytes.extend_from_slice(&self.block_number.to_le_bytes());
+        bytes.extend_from_slice(&self.event_index.to_le_bytes());
+        encoding::put_bytes(&mut bytes, self.recipient.as_bytes());
+        bytes.extend_from_slice(&self.amount.to_le_bytes());
+        bytes
+    }
+
+    pub fn leaf_hash(&self) -> Hash {
+        let mut hasher = Sha256::new();
+        hasher.update(b"hvm-deposit");
+        hasher.update(self.block_number.to_le_bytes());
+        hasher.update(self.event_index.to_le_bytes());
+        hasher.update((self.recipient.len() as u32).to_le_bytes());
+        hasher.update(self.recipient.as_bytes());
+        hasher.update(self.amount.to_le_bytes());
+        hasher.finalize().into()
+    }
+}
+
+pub trait DepositSource {
+    fn poll_deposits(&mut self, after: Option<DepositId>) -> Result<Vec<L1Deposit>, HVMError>;
+}
+
+#[derive(Default)]
+pub struct MockDepositSource {
+    events: Vec<L1Deposit>,
+}
+
+impl MockDepositSource {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, deposit: L1Deposit) {
+        self.events.push(deposit);
+        self.events.sort_by_key(L1Deposit::id);
+    }
+}
+
+impl DepositSource for MockDepositSource {
+    fn poll_deposits(&mut self, after: Option<DepositId>) -> Result<Vec<L1Deposit>, HVMError> {
+        Ok(self.events
+            .iter()
+            .filter(|deposit| after.is_none_or(|cursor| deposit.id() > cursor))
+            .cloned()
+            .collect())
+    }
+}
+
+#[derive(Default)]
+pub struct DepositQueue {
+    pending: VecDeque<L1Deposit>,
+    cursor: Option<DepositId>,
+}
+
+impl DepositQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cursor(&self) -> Option<DepositId> {
+        self.cursor
+    }
+
+    pub fn enqueue(&mut self, deposit: L1Deposit) -> bool {
+        if self.cursor.is_some_and(|cursor| deposit.id() <= cursor) {
+            return false;
+        }
+        self.cursor = Some(deposit.id());
+        self.pending.push_back(deposit);
+        true
+    }
+
+    pub fn advance_cursor(&mut self, id: DepositId) {
+        if self.cursor.is_none_or(|cursor| id > cursor) {
+            self.cursor = Some(id);
+        }
+    }
+
+    pub fn take(&mut self, max: usize) -> Vec<L1Deposit> {
+        let count = max.min(self.pending.len());
+        self.pending.drain(..count).collect()
+    }
+
+    pub fn requeue(&mut self, deposits: Vec<L1Deposit>) {
+        for deposit in deposits.into_iter().rev() {
+            self.pending.push_front(deposit);
+        }
+    }
+
+    pub fn remove(&mut self, deposits: &[L1Deposit]) {
+        self.pending.retain(|pending| !deposits.contains(pending));
+    }
+
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    pub fn pending(&self) -> &VecDeque<L1Deposit> {
+        &self.pending
+    }
+}
\ No newline at end of file
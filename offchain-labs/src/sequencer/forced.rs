@@ -0,0 +1,148 @@
+use super::transaction::Transaction;
+use crate::error::HVMError;
+use crate::zk_rollup::merkle::Hash;
+use serde::{Serialize, Deserialize};
+use std::collections::{HashSet, VecDeque};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct ForcedId {
+    pub block_number: u64,
+    pub event_index: u32,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ForcedTransaction {
+    pub block_number: u64,
+    pub event_index: u32,
+    pub transaction: Transaction,
+}
+
+impl ForcedTransaction {
+    pub fn new(block_number: u64, event_index: u32, transaction: Transaction) -> Self {
+        Self { block_number, event_index, transaction }
+    }
+
+    pub fn id(&self) -> ForcedId {
+        ForcedId { block_number: self.block_number, event_index: self.event_index }
+    }
+}
+
+pub trait ForcedInclusionSource {
+    fn poll_forced(&mut self, after: Option<ForcedId>) -> Result<Vec<ForcedTransaction>, HVMError>;
+}
+
+#[derive(Default)]
+pub struct MockForcedInclusionSource {
+    messages: Vec<ForcedTransaction>,
+}
+
+impl MockForcedInclusionSource {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, forced: ForcedTransaction) {
+        self.messages.push(forced);
+        self.messages.sort_by_key(ForcedTransaction::id);
+    }
+}
+
+impl ForcedInclusionSource for MockForcedInclusionSource {
+    fn poll_forced(&mut self, after: Option<ForcedId>) -> Result<Vec<ForcedTransaction>, HVMError> {
+        Ok(self.messages
+            .iter()
+            .filter(|forced| after.is_none_or(|cursor| forced.id() > cursor))
+            .cloned()
+            .collect())
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ForcedEntry {
+    pub forced: ForcedTransaction,
+    pub hash: Hash,
+    pub deadline: u64,
+    pub sealed_in: Option<u64>,
+}
+
+#[derive(Default)]
+pub struct ForcedQueue {
+    entries: VecDeque<ForcedEntry>,
+    cursor: Option<ForcedId>,
+}
+
+impl ForcedQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cursor(&self) -> Option<ForcedId> {
+        self.cursor
+    }
+
+    pub fn advance_cursor(&mut self, id: ForcedId) -> bool {
+        if self.cursor.is_some_and(|cursor| id <= cursor) {
+            return false;
+        }
+        self.cursor = Some(id);
+        true
+    }
+
+    pub fn push(&mut self, forced: ForcedTransaction, deadline: u64) {
+        let hash = forced.transaction.hash();
+        self.entries.push_back(ForcedEntry { forced, hash, deadline, sealed_in: None });
+    }
+
+    pub fn requeue(&mut self, entries: Vec<ForcedEntry>) {
+        for mut entry in entries.into_iter().rev() {
+            entry.sealed_in = None;
+            self.entries.push_front(entry);
+        }
+    }
+
+    pub fn unsealed(&self) -> impl Iterator<Item = &ForcedEntry> {
+        self.entries.iter().filter(|entry| entry.sealed_in.is_none())
+    }
+
+    pub fn mark_sealed(&mut self, height: u64, included: &HashSet<Hash>) {
+        for entry in self.entries.iter_mut().filter(|entry| included.contains(&entry.hash)) {
+            entry.sealed_in = Some(height);
+        }
+    }
+
+    pub fn unseal_from(&mut self, height: u64) {
+        for entry in self.entries.iter_mut().filter(|entry| entry.sealed_in.is_some_and(|sealed| sealed >= height)) {
+            entry.sealed_in = None;
+        }
+    }
+
+    pub fn last_deadline(&self) -> Option<u64> {
+        self.entries.back().map(|entry| entry.deadline)
+    }
+
+    pub fn due_at(&self, deadline: u64) -> impl Iterator<Item = &ForcedEntry> {
+        self.entries.iter().filter(move |entry| entry.deadline == deadline)
+    }
+
+    pub fn overdue(&self, height: u64, included: &HashSet<Hash>) -> Option<&ForcedEntry> {
+        self.entries.iter().find(|entry| entry.deadline <= height && !included.contains(&entry.hash))
+    }
+
+    pub fn remove_included(&mut self, included: &HashSet<Hash>) -> Vec<ForcedEntry> {
+        let (taken, kept): (VecDeque<_>, VecDeque<_>) = self.entries.drain(..).partition(|entry| included.contains(&entry.hash));
+        self.entries = kept;
+        taken.into()
+    }
+
+    pub fn contains(&self, hash: &Hash) -> bool {
+        self.entries.iter().any(|entry| entry.hash == *hash)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
\ No newline at end of file
@@ -0,0 +1,191 @@
+use super::admission::RejectionReason;
+use super::transaction::Transaction;
+use crate::error::HVMError;
+use crate::zk_rollup::merkle::Hash;
+use crate::zk_rollup::State;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+pub const REPLACEMENT_FEE_BUMP_PERCENT: u128 = 10;
+
+#[derive(Clone, Debug)]
+struct PooledTransaction {
+    transaction: Transaction,
+    hash: Hash,
+    arrival: u64,
+}
+
+#[derive(Default)]
+struct SenderQueue {
+    next_nonce: u64,
+    ready: BTreeMap<u64, PooledTransaction>,
+    future: BTreeMap<u64, PooledTransaction>,
+}
+
+impl SenderQueue {
+    fn new(next_nonce: u64) -> Self {
+        Self { next_nonce, ..Self::default() }
+    }
+
+    fn ready_end(&self) -> u64 {
+        self.next_nonce + self.ready.len() as u64
+    }
+
+    fn promote(&mut self) {
+        while let Some(pooled) = self.future.remove(&self.ready_end()) {
+            self.ready.insert(pooled.transaction.nonce, pooled);
+        }
+    }
+
+    fn rebase(&mut self, next_nonce: u64) {
+        let mut pooled: BTreeMap<u64, PooledTransaction> = std::mem::take(&mut self.ready);
+        pooled.append(&mut self.future);
+        self.next_nonce = next_nonce;
+        self.future = pooled.split_off(&next_nonce);
+        self.promote();
+    }
+
+    fn get(&self, nonce: u64) -> Option<&Transaction> {
+        self.ready.get(&nonce).or_else(|| self.future.get(&nonce)).map(|pooled| &pooled.transaction)
+    }
+
+    fn len(&self) -> usize {
+        self.ready.len() + self.future.len()
+    }
+}
+
+#[derive(Default)]
+pub struct Mempool {
+    senders: HashMap<String, SenderQueue>,
+    arrivals: u64,
+}
+
+impl Mempool {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn check_replacement(&self, transaction: &Transaction) -> Result<(), RejectionReason> {
+        let Some(existing) = self.senders.get(&transaction.sender).and_then(|queue| queue.get(transaction.nonce)) else {
+            return Ok(());
+        };
+        let bump = |fee: u128| fee.saturating_mul(REPLACEMENT_FEE_BUMP_PERCENT).div_ceil(100);
+        let min_max_fee = existing.max_fee.saturating_add(bump(existing.max_fee).max(1));
+        let min_priority_tip = existing.priority_tip.saturating_add(bump(existing.priority_tip));
+        if transaction.max_fee < min_max_fee || transaction.priority_tip < min_priority_tip {
+            return Err(RejectionReason::ReplacementUnderpriced { min_max_fee, min_priority_tip });
+        }
+        Ok(())
+    }
+
+    pub fn check_nonce(&self, transaction: &Transaction, account_nonce: u64) -> Result<(), RejectionReason> {
+        let expected = self.senders.get(&transaction.sender).map_or(account_nonce, |queue| queue.next_nonce);
+        if transaction.nonce < expected {
+            return Err(RejectionReason::NonceTooLow { expected, got: transaction.nonce });
+        }
+        Ok(())
+    }
+
+    pub fn insert(&mut self, transaction: Transaction, account_nonce: u64) -> Result<Option<Transaction>, HVMError> {
+        self.check_replacement(&transaction)?;
+        self.check_nonce(&transaction, account_nonce)?;
+        let queue = self.senders
+            .entry(transaction.sender.clone())
+            .or_insert_with(|| SenderQueue::new(account_nonce));
+
+        self.arrivals += 1;
+        let nonce = transaction.nonce;
+        let hash = transaction.hash();
+        let pooled = PooledTransaction { transaction, hash, arrival: self.arrivals };
+        let replaced = if nonce < queue.ready_end() {
+            queue.ready.insert(nonce, pooled)
+        } else {
+            queue.future.insert(nonce, pooled)
+        };
+        queue.promote();
+
+        Ok(replaced.map(|pooled| pooled.transaction))
+    }
+
+    pub fn take_ready(&mut self, max: usize, base_fee: u128) -> Vec<Transaction> {
+        self.take_ready_within(max, base_fee, |_| true)
+    }
+
+    pub fn take_ready_within<F>(&mut self, max: usize, base_fee: u128, mut fits: F) -> Vec<Transaction>
+    where
+        F: FnMut(&Transaction) -> bool,
+    {
+        let mut taken = Vec::new();
+        let mut skipped = HashSet::new();
+        while taken.len() < max {
+            let next = self.senders
+                .iter()
+                .filter(|(sender, _)| !skipped.contains(*sender))
+                .filter_map(|(sender, queue)| {
+                    queue.ready.values().next().map(|pooled| {
+                        let fee = pooled.transaction.effective_fee(base_fee).unwrap_or(0);
+                        (fee, Reverse(pooled.arrival), sender.clone())
+                    })
+                })
+                .max();
+            let Some((_, _, sender)) = next else {
+                break;
+            };
+
+            let queue = self.senders.get_mut(&sender).unwrap();
+            let (_, head) = queue.ready.first_key_value().unwrap();
+            if !fits(&head.transaction) {
+                skipped.insert(sender);
+                continue;
+            }
+            let (_, pooled) = queue.ready.pop_first().unwrap();
+            queue.next_nonce += 1;
+            taken.push(pooled.transaction);
+        }
+        taken
+    }
+
+    pub fn resync(&mut self, state: &State) {
+        for (sender, queue) in self.senders.iter_mut() {
+            let account_nonce = state.get_account(sender).map_or(0, |account| account.nonce);
+            queue.rebase(account_nonce);
+        }
+        self.senders.retain(|_, queue| queue.len() > 0);
+    }
+
+    pub fn ready_transactions(&self) -> impl Iterator<Item = &Transaction> {
+        self.senders.values().flat_map(|queue| queue.ready.values().map(|pooled| &pooled.transaction))
+    }
+
+    pub fn contains(&self, hash: &Hash) -> bool {
+        self.senders
+            .values()
+            .flat_map(|queue| queue.ready.values().chain(queue.future.values()))
+            .any(|pooled| pooled.hash == *hash)
+    }
+
+    pub fn len(&self) -> usize {
+        self.senders.values().map(SenderQueue::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn ready_len(&self) -> usize {
+        self.senders.values().map(|queue| queue.ready.len()).sum()
+    }
+
+    pub fn future_len(&self) -> usize {
+        self.senders.values().map(|queue| queue.future.len()).sum()
+    }
+
+    pub fn transactions(&self) -> Vec<&Transaction> {
+        let mut transactions: Vec<&PooledTransaction> = self.senders
+            .values()
+            .flat_map(|queue| queue.ready.values().chain(queue.future.values()))
+            .collect();
+        transactions.sort_by_key(|pooled| pooled.arrival);
+        transactions.into_iter().map(|pooled| &pooled.transaction).collect()
+    }
+}
\ No newline at end of file
@@ -1,24 +1,68 @@
 use crate::error::HVMError;
-use crate::zk_rollup::{Proof, State};
-use crate::config::SequencerConfig;
-use crate::bend::BendProgram;
+use crate::zk_rollup::{merkle, transition, withdrawal, State, StateDiff, TouchedAccounts, TransactionOutcome, Withdrawal, WithdrawalClaim};
+use crate::config::{ProvingFailurePolicy, SequencerConfig};
+use crate::bend::{self, BendProgram};
+use log::{debug, error, info, warn};
 use std::time::{Duration, Instant};
-use std::collections::{VecDeque, HashMap};
-use ark_serialize::CanonicalSerialize;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::path::Path;
 
+pub mod admission;
 pub mod batch;
+pub mod deposit;
+pub mod forced;
+pub mod mempool;
+pub mod receipt;
+pub mod service;
+pub mod signing;
+pub mod snapshot;
 pub mod transaction;
+pub mod wal;
+pub mod weight;
 
-pub use batch::Batch;
-pub use transaction::Transaction;
+pub use admission::{
+    AdmissionContext, AdmissionPipeline, AdmissionValidator, BalanceValidator, DuplicateValidator, NonceValidator,
+    PayloadSizeValidator, ProgramValidator, RejectionReason,
+};
+pub use batch::{verify_chain, Batch, BatchHeader};
+pub use deposit::{DepositId, DepositQueue, DepositSource, L1Deposit, MockDepositSource};
+pub use forced::{ForcedEntry, ForcedId, ForcedInclusionSource, ForcedQueue, ForcedTransaction, MockForcedInclusionSource};
+pub use mempool::{Mempool, REPLACEMENT_FEE_BUMP_PERCENT};
+pub use receipt::{DeploymentReceipt, ReceiptStore, TransactionReceipt, TransactionStatus};
+pub use service::{ProvenBatch, ProvingStage, SealedBatch, SequencerHandle, SequencerService};
+pub use signing::{SignatureScheme, TransactionSignature, TransactionSigner};
+pub use snapshot::{SnapshotJournal, StateSnapshot};
+pub use transaction::{Transaction, TransactionKind, TRANSACTION_FORMAT_VERSION};
+pub use wal::{WalRecord, WriteAheadLog};
+pub use weight::BatchWeight;
 
 pub struct Sequencer {
     state: State,
-    pending_transactions: VecDeque<Transaction>,
+    sealed_state: State,
+    sealed_head: Option<BatchHeader>,
+    in_flight: BTreeMap<u64, Batch>,
+    sealed_accounts: BTreeMap<u64, TouchedAccounts>,
+    retry_groups: VecDeque<Vec<Transaction>>,
+    headers: BTreeMap<u64, BatchHeader>,
+    batch_heights: HashMap<merkle::Hash, u64>,
+    pending_deposits: DepositQueue,
+    forced: ForcedQueue,
+    pending_transactions: Mempool,
     processed_transactions: Vec<Transaction>,
     pending_programs: VecDeque<BendProgram>,
     processed_programs: Vec<BendProgram>,
     deployed_programs: HashMap<String, BendProgram>,
+    program_batches: HashMap<merkle::Hash, Vec<String>>,
+    snapshots: SnapshotJournal,
+    withdrawals: HashMap<merkle::Hash, Vec<Withdrawal>>,
+    rejected_deposits: HashMap<merkle::Hash, Vec<DepositId>>,
+    included_deposits: HashMap<merkle::Hash, Vec<L1Deposit>>,
+    included_forced: HashMap<merkle::Hash, Vec<ForcedEntry>>,
+    batch_fees: HashMap<merkle::Hash, u128>,
+    collected_fees: u128,
+    receipts: ReceiptStore,
+    admission: AdmissionPipeline,
+    wal: Option<WriteAheadLog>,
     config: SequencerConfig,
     last_batch_time: Instant,
 }
@@ -26,41 +70,146 @@ pub struct Sequencer {
 impl Sequencer {
     pub fn new(initial_state: State, config: SequencerConfig) -> Self {
         Self {
+            sealed_state: initial_state.clone(),
             state: initial_state,
-            pending_transactions: VecDeque::new(),
+            sealed_head: None,
+            in_flight: BTreeMap::new(),
+            sealed_accounts: BTreeMap::new(),
+            retry_groups: VecDeque::new(),
+            headers: BTreeMap::new(),
+            batch_heights: HashMap::new(),
+            pending_deposits: DepositQueue::new(),
+            forced: ForcedQueue::new(),
+            pending_transactions: Mempool::new(),
             processed_transactions: Vec::new(),
             pending_programs: VecDeque::new(),
             processed_programs: Vec::new(),
             deployed_programs: HashMap::new(),
+            program_batches: HashMap::new(),
+            snapshots: SnapshotJournal::new(config.max_retained_snapshots),
+            withdrawals: HashMap::new(),
+            rejected_deposits: HashMap::new(),
+            included_deposits: HashMap::new(),
+            included_forced: HashMap::new(),
+            batch_fees: HashMap::new(),
+            collected_fees: 0,
+            receipts: ReceiptStore::new(),
+            admission: AdmissionPipeline::standard(config.max_transaction_bytes),
+            wal: None,
             config,
             last_batch_time: Instant::now(),
         }
     }
 
     pub fn process_transaction(&mut self, transaction: Transaction) -> Result<(), HVMError> {
+        transaction.verify_signature()?;
+        if transaction.chain_id != self.config.chain_id {
+            return Err(RejectionReason::WrongChain { expected: self.config.chain_id, got: transaction.chain_id }.into());
+        }
         if self.pending_transactions.len() >= self.config.max_pending_transactions {
             return Err(HVMError::Sequencer("Max pending transactions reached".to_string()));
         }
-        self.pending_transactions.push_back(transaction);
+        let context = AdmissionContext {
+            state: &self.state,
+            deployed_programs: &self.deployed_programs,
+            mempool: &self.pending_transactions,
+            base_fee: self.config.base_fee,
+        };
+        self.admission.validate(&transaction, &context)?;
+        if !(BatchWeight::empty(&self.config) + BatchWeight::of_transaction(&transaction)).fits(&self.config) {
+            return Err(RejectionReason::ExceedsBatchLimits.into());
+        }
+        let account_nonce = self.sealed_state.get_account(&transaction.sender).map_or(0, |account| account.nonce);
+        self.pending_transactions.check_replacement(&transaction)?;
+        self.pending_transactions.check_nonce(&transaction, account_nonce)?;
+        self.log(WalRecord::Admitted(transaction.clone()))?;
+        let hash = transaction.hash();
+        if let Some(replaced) = self.pending_transactions.insert(transaction, account_nonce)? {
+            self.receipts.fail(&replaced.hash(), format!("Replaced by {}", hex::encode(hash)));
+        }
+        self.receipts.pending(hash);
         Ok(())
     }
 
+    pub fn set_admission_pipeline(&mut self, pipeline: AdmissionPipeline) {
+        self.admission = pipeline;
+    }
+
+    pub fn add_validator<V: AdmissionValidator + 'static>(&mut self, validator: V) {
+        self.admission.push(validator);
+    }
+
+    pub fn submit_deposit(&mut self, deposit: L1Deposit) -> bool {
+        self.pending_deposits.enqueue(deposit)
+    }
+
+    pub fn ingest_deposits<S: DepositSource>(&mut self, source: &mut S) -> Result<usize, HVMError> {
+        let deposits = source.poll_deposits(self.pending_deposits.cursor())?;
+        Ok(deposits.into_iter().filter(|deposit| self.pending_deposits.enqueue(deposit.clone())).count())
+    }
+
+    pub fn submit_forced(&mut self, forced: ForcedTransaction) -> bool {
+        if !self.forced.advance_cursor(forced.id())
+            || forced.transaction.chain_id != self.config.chain_id
+            || forced.transaction.verify_signature().is_err()
+            || !(BatchWeight::empty(&self.config) + BatchWeight::of_transaction(&forced.transaction)).fits(&self.config)
+        {
+            return false;
+        }
+        let hash = forced.transaction.hash();
+        if self.forced.contains(&hash) || self.receipts.get(&hash).is_some_and(|receipt| receipt.status != TransactionStatus::Pending) {
+            return false;
+        }
+        let deadline = self.forced_deadline(&forced.transaction);
+        if let Err(e) = self.log(WalRecord::Forced(forced.clone(), deadline)) {
+            error!("Failed to record forced transaction {}: {}", hex::encode(hash), e);
+            return false;
+        }
+        self.receipts.pending(hash);
+        self.forced.push(forced, deadline);
+        true
+    }
+
+    fn forced_deadline(&self, transaction: &Transaction) -> u64 {
+        let earliest = self.sealed_head.as_ref().map_or(0, |header| header.height + 1) + self.config.forced_inclusion_window.max(1) - 1;
+        let Some(last) = self.forced.last_deadline().filter(|last| *last >= earliest) else {
+            return earliest;
+        };
+        let due = BatchWeight::empty(&self.config)
+            + self.forced.due_at(last).map(|entry| BatchWeight::of_transaction(&entry.forced.transaction)).sum()
+            + BatchWeight::of_transaction(transaction);
+        if due.fits(&self.config) { last } else { last + 1 }
+    }
+
+    pub fn ingest_forced<S: ForcedInclusionSource>(&mut self, source: &mut S) -> Result<usize, HVMError> {
+        let messages = source.poll_forced(self.forced.cursor())?;
+        Ok(messages.into_iter().filter(|forced| self.submit_forced(forced.clone())).count())
+    }
+
     pub fn submit_program(&mut self, program: BendProgram) -> Result<(), HVMError> {
         if self.pending_programs.len() >= self.config.max_pending_programs {
             return Err(HVMError::Sequencer("Max pending programs reached".to_string()));
         }
+        if self.deployed_programs.contains_key(program.id()) || self.pending_programs.iter().any(|pending| pending.id() == program.id()) {
+            return Err(HVMError::Sequencer(format!("Program already submitted: {}", program.id())));
+        }
+        if !(BatchWeight::empty(&self.config) + BatchWeight::of_program(&program)).fits(&self.config) {
+            return Err(HVMError::Sequencer(format!("Program {} exceeds the batch resource limits", program.id())));
+        }
+        self.receipts.program_pending(program.id().to_string());
         self.pending_programs.push_back(program);
         Ok(())
     }
 
     pub fn deploy_program(&mut self, program: BendProgram) -> Result<(), HVMError> {
+        self.log(WalRecord::Deployed(program.clone()))?;
         let program_id = program.id().to_string();
         self.deployed_programs.insert(program_id, program);
         Ok(())
     }
 
     pub fn create_batch(&mut self, force: bool) -> Result<Option<Batch>, HVMError> {
-        if self.pending_transactions.is_empty() && self.pending_programs.is_empty() {
+        if !self.has_pending_work() {
             return Ok(None);
         }
     
@@ -69,68 +218,606 @@ impl Sequencer {
             return Ok(None);
         }
     
-        let mut batch_transactions = Vec::new();
-        let mut batch_programs = Vec::new();
+        let config = &self.config;
+        let mut weight = BatchWeight::empty(config);
+        let mut batch_transactions: Vec<Transaction> = self
+            .forced
+            .unsealed()
+            .take(config.max_batch_size)
+            .take_while(|entry| {
+                let next = weight + BatchWeight::of_transaction(&entry.forced.transaction);
+                next.fits(config) && {
+                    weight = next;
+                    true
+                }
+            })
+            .map(|entry| entry.forced.transaction.clone())
+            .collect();
+        let forced_count = batch_transactions.len();
+        let retried = !self.retry_groups.is_empty();
+        let (batch_deposits, selected, batch_programs) = match self.retry_groups.pop_front() {
+            Some(group) => (Vec::new(), self.fit_retry_group(group, weight, forced_count), Vec::new()),
+            None => self.select_contents(weight, forced_count),
+        };
+        batch_transactions.extend(selected);
+        if batch_deposits.is_empty() && batch_transactions.is_empty() && batch_programs.is_empty() {
+            return Ok(None);
+        }
+    
+        let draft = Batch::with_deposits(batch_deposits, batch_transactions, batch_programs)
+            .with_parent(self.sealed_head.as_ref())
+            .with_chain_id(self.config.chain_id)
+            .with_base_fee(self.config.base_fee)
+            .with_fee_recipient(self.config.fee_recipient.clone());
+        let transition = match transition::apply_batch(&self.sealed_state, &draft, &self.deployed_programs) {
+            Ok(transition) => transition,
+            Err(e) => {
+                self.restore_unsealed(&draft, forced_count, retried);
+                return Err(e);
+            }
+        };
+        let batch = draft
+            .with_rejected_deposits(transition.rejected_deposits.iter().map(|(id, _)| *id).collect())
+            .with_state_roots(self.sealed_state.root(), transition.post_state.root())
+            .with_withdrawals_root(transition.withdrawal_root());
+        if let Err(e) = self.log(WalRecord::Sealed(batch.clone())) {
+            self.restore_unsealed(&batch, forced_count, retried);
+            return Err(e);
+        }
+        for (id, reason) in &transition.rejected_deposits {
+            warn!("Deposit {}:{} cannot be credited: {}", id.block_number, id.event_index, reason);
+        }
+        self.receipts.batched(&batch);
 
-        while let Some(tx) = self.pending_transactions.pop_front() {
-            batch_transactions.push(tx);
-            if batch_transactions.len() >= self.config.max_batch_size {
-                break;
+        self.sealed_accounts.insert(batch.height(), transition.touched_accounts());
+        self.sealed_state = transition.post_state;
+        self.sealed_head = Some(batch.header().clone());
+        self.in_flight.insert(batch.height(), batch.clone());
+        self.forced.mark_sealed(batch.height(), &batch.transactions().iter().map(Transaction::hash).collect());
+        self.last_batch_time = now;
+        Ok(Some(batch))
+    }
+
+    fn restore_unsealed(&mut self, batch: &Batch, forced_count: usize, retried: bool) {
+        self.pending_deposits.requeue(batch.deposits().to_vec());
+        for program in batch.programs().iter().rev() {
+            self.pending_programs.push_front(program.clone());
+        }
+        let transactions = batch.transactions()[forced_count..].to_vec();
+        if retried {
+            if !transactions.is_empty() {
+                self.retry_groups.push_front(transactions);
+            }
+            return;
+        }
+        self.pending_transactions.resync(&self.sealed_state);
+        for transaction in transactions {
+            let account_nonce = self.sealed_state.get_account(&transaction.sender).map_or(0, |account| account.nonce);
+            if let Err(e) = self.pending_transactions.insert(transaction, account_nonce) {
+                error!("Failed to restore unsealed transaction: {}", e);
             }
         }
+    }
+
+    fn fit_retry_group(&mut self, mut group: Vec<Transaction>, mut weight: BatchWeight, reserved: usize) -> Vec<Transaction> {
+        let config = &self.config;
+        let fitting = group
+            .iter()
+            .take(config.max_batch_size.saturating_sub(reserved))
+            .take_while(|transaction| {
+                let next = weight + BatchWeight::of_transaction(transaction);
+                next.fits(config) && {
+                    weight = next;
+                    true
+                }
+            })
+            .count();
+        let rest = group.split_off(fitting);
+        if !rest.is_empty() {
+            self.retry_groups.push_front(rest);
+        }
+        group
+    }
+
+    fn select_contents(&mut self, mut weight: BatchWeight, reserved: usize) -> (Vec<L1Deposit>, Vec<Transaction>, Vec<BendProgram>) {
+        let config = &self.config;
+        let capacity = config.max_batch_size.saturating_sub(reserved);
+        let deposit_count = self
+            .pending_deposits
+            .pending()
+            .iter()
+            .take(capacity)
+            .take_while(|deposit| {
+                let next = weight + BatchWeight::of_deposit(deposit);
+                next.fits(config) && {
+                    weight = next;
+                    true
+                }
+            })
+            .count();
+        let batch_deposits = self.pending_deposits.take(deposit_count);
 
-        while let Some(program) = self.pending_programs.pop_front() {
-            batch_programs.push(program);
-            if batch_programs.len() >= self.config.max_programs_per_batch {
+        let mut batch_programs = Vec::new();
+        while let Some(program) = self.pending_programs.front() {
+            let next = weight + BatchWeight::of_program(program);
+            if batch_programs.len() >= config.max_programs_per_batch || !next.fits(config) {
                 break;
             }
+            weight = next;
+            batch_programs.extend(self.pending_programs.pop_front());
         }
-    
-        let batch = Batch::new(batch_transactions, batch_programs);
-        self.last_batch_time = now;
-        Ok(Some(batch))
+
+        let forced = &self.forced;
+        let batch_transactions = self.pending_transactions.take_ready_within(
+            capacity - batch_deposits.len(),
+            config.base_fee,
+            |transaction| {
+                if forced.contains(&transaction.hash()) {
+                    return false;
+                }
+                let next = weight + BatchWeight::of_transaction(transaction);
+                next.fits(config) && {
+                    weight = next;
+                    true
+                }
+            },
+        );
+        (batch_deposits, batch_transactions, batch_programs)
     }
 
-    pub fn apply_proof(&mut self, proof: Proof, batch: &Batch) -> Result<(), HVMError> {
-        println!("Applying proof in sequencer: {:?}", proof);
-        let result = self.state.apply_proof(&proof);
-        println!("State after applying proof: {:?}", self.state);
+    pub fn execute_batch(&mut self, batch: &Batch) -> Result<Vec<TransactionOutcome>, HVMError> {
+        let transition = transition::apply_batch(&self.state, batch, &self.deployed_programs)?;
+        let touched = transition.touched_accounts();
+        self.commit(transition.post_state, batch, &transition.outcomes, touched)?;
+        Ok(transition.outcomes)
+    }
+
+    pub fn commit_batch(&mut self, post_state: State, batch: &Batch, outcomes: &[TransactionOutcome]) -> Result<(), HVMError> {
+        let touched = match self.sealed_accounts.get(&batch.height()).filter(|_| self.is_in_flight(batch)) {
+            Some(touched) => touched.clone(),
+            None => transition::apply_batch(&self.state, batch, &self.deployed_programs)?.touched_accounts(),
+        };
+        self.commit(post_state, batch, outcomes, touched)
+    }
+
+    fn commit(&mut self, post_state: State, batch: &Batch, outcomes: &[TransactionOutcome], touched: TouchedAccounts) -> Result<(), HVMError> {
+        let header = batch.header();
+        let extends_head = match self.headers.values().next_back() {
+            Some(head) => header.extends(head),
+            None => header.height == 0 && header.parent_hash == [0u8; 32],
+        };
+        if header.chain_id != self.config.chain_id {
+            return Err(HVMError::Sequencer(format!(
+                "Batch {} belongs to chain {}, expected chain {}",
+                merkle::to_hex(&batch.batch_id()),
+                header.chain_id,
+                self.config.chain_id
+            )));
+        }
+        if !extends_head || header.pre_state_root != self.state.root() {
+            return Err(HVMError::Sequencer(format!("Batch {} does not extend the committed chain", merkle::to_hex(&batch.batch_id()))));
+        }
+        if header.post_state_root != post_state.root() {
+            return Err(HVMError::Sequencer(format!("Batch {} does not reach its declared post state root", merkle::to_hex(&batch.batch_id()))));
+        }
+        if !batch.verify_contents() {
+            return Err(HVMError::Sequencer(format!("Batch {} contents do not match its header", merkle::to_hex(&batch.batch_id()))));
+        }
+        let withdrawals = withdrawal::collect_withdrawals(batch, outcomes);
+        if header.withdrawals_root != withdrawal::withdrawal_root(&withdrawals) {
+            return Err(HVMError::Sequencer(format!("Batch {} does not commit to its withdrawals", merkle::to_hex(&batch.batch_id()))));
+        }
+        let included: HashSet<merkle::Hash> = batch.transactions().iter().map(Transaction::hash).collect();
+        if let Some(entry) = self.forced.overdue(header.height, &included) {
+            return Err(HVMError::Sequencer(format!(
+                "Batch {} skips forced transaction {} due by batch {}",
+                merkle::to_hex(&batch.batch_id()),
+                hex::encode(entry.hash),
+                entry.deadline
+            )));
+        }
+        self.log(WalRecord::Committed(batch.batch_id()))?;
+
+        self.snapshots.record(header, touched, self.processed_transactions.len());
+        self.state = post_state;
+
+        if !withdrawals.is_empty() {
+            self.withdrawals.insert(batch.batch_id(), withdrawals);
+        }
+        if !batch.deposits().is_empty() {
+            self.included_deposits.insert(batch.batch_id(), batch.deposits().to_vec());
+        }
+        self.included_deposits.retain(|batch_id, _| self.snapshots.contains(batch_id));
+        if !batch.rejected_deposits().is_empty() {
+            self.rejected_deposits.insert(batch.batch_id(), batch.rejected_deposits().to_vec());
+        }
+        let forced = self.forced.remove_included(&included);
+        if !forced.is_empty() {
+            self.included_forced.insert(batch.batch_id(), forced);
+        }
+        self.included_forced.retain(|batch_id, _| self.snapshots.contains(batch_id));
+        debug!("State root after batch {}: {}", merkle::to_hex(&batch.batch_id()), merkle::to_hex(&self.state.root()));
         
+        let fees: u128 = batch
+            .transactions()
+            .iter()
+            .zip(outcomes)
+            .filter(|(_, outcome)| outcome.success)
+            .map(|(tx, _)| tx.effective_fee(batch.base_fee()).unwrap_or(0))
+            .sum();
+        self.batch_fees.insert(batch.batch_id(), fees);
+        self.batch_fees.retain(|batch_id, _| self.snapshots.contains(batch_id));
+        self.collected_fees += fees;
+        self.receipts.proven(batch, outcomes);
+        let deployed = transition::collect_deployments(batch, outcomes);
+        if !deployed.is_empty() {
+            for program in &deployed {
+                self.deployed_programs.insert(program.id().to_string(), (*program).clone());
+                self.processed_programs.push((*program).clone());
+            }
+            self.program_batches.insert(batch.batch_id(), deployed.iter().map(|program| program.id().to_string()).collect());
+        }
+        self.program_batches.retain(|batch_id, _| self.snapshots.contains(batch_id));
         for tx in batch.transactions() {
             self.processed_transactions.push(tx.clone());
         }
-        
-        result
+        self.headers.insert(header.height, header.clone());
+        self.batch_heights.insert(batch.batch_id(), header.height);
+        self.in_flight.remove(&header.height);
+        self.sealed_accounts.remove(&header.height);
+        self.pending_transactions.resync(&self.sealed_state);
+        Ok(())
+    }
+
+    pub fn abandon_sealed(&mut self) {
+        if self.sealed_head.as_ref() != self.headers.values().next_back() {
+            if let Err(e) = self.log(WalRecord::Abandoned) {
+                error!("Failed to record abandoned batches: {}", e);
+            }
+            self.receipts.discard_from(self.next_height(), "abandoned");
+        }
+        self.unseal_forced(self.next_height());
+        self.sealed_accounts.clear();
+        for batch in std::mem::take(&mut self.in_flight).into_values().rev() {
+            self.pending_deposits.requeue(batch.deposits().to_vec());
+        }
+        self.sealed_state = self.state.clone();
+        self.sealed_head = self.headers.values().next_back().cloned();
+    }
+
+    fn unseal_forced(&mut self, height: u64) {
+        self.forced.unseal_from(height);
+        for hash in self.forced.unsealed().map(|entry| entry.hash).collect::<Vec<_>>() {
+            self.receipts.pending(hash);
+        }
+    }
+
+    pub fn in_flight_count(&self) -> usize {
+        self.in_flight.len()
+    }
+
+    pub fn is_in_flight(&self, batch: &Batch) -> bool {
+        self.in_flight.get(&batch.height()).is_some_and(|sealed| sealed.batch_id() == batch.batch_id())
+    }
+
+    pub fn handle_proving_failure(&mut self, batch_id: &merkle::Hash, reason: &str) {
+        let Some(height) = self.in_flight.values().find(|batch| batch.batch_id() == *batch_id).map(Batch::height) else {
+            return;
+        };
+        let failed: Vec<Batch> = self.in_flight.split_off(&height).into_values().collect();
+        self.sealed_accounts.split_off(&height);
+        let first = &failed[0];
+        if let Err(e) = self.log(WalRecord::Abandoned) {
+            error!("Failed to record abandoned batches: {}", e);
+        }
+        self.receipts.discard_from(height, &format!("rejected by the prover: {}", reason));
+
+        self.unseal_forced(height);
+
+        let unforced = |batch: &Batch| -> Vec<Transaction> {
+            batch.transactions().iter().filter(|transaction| !self.forced.contains(&transaction.hash())).cloned().collect()
+        };
+        let mut groups = Vec::new();
+        let mut programs = Vec::new();
+        let policy = self.config.proving_failure_policy;
+        let transactions = unforced(first);
+        if policy == ProvingFailurePolicy::Requeue && transactions.len() > 1 {
+            let (left, right) = transactions.split_at(transactions.len() / 2);
+            groups.push(left.to_vec());
+            groups.push(right.to_vec());
+        }
+        if policy == ProvingFailurePolicy::Requeue && !transactions.is_empty() {
+            programs.extend(first.programs().iter().cloned());
+        }
+        for batch in &failed[1..] {
+            let transactions = unforced(batch);
+            if !transactions.is_empty() {
+                groups.push(transactions);
+            }
+            programs.extend(batch.programs().iter().cloned());
+        }
+        for batch in failed.iter().rev() {
+            self.pending_deposits.requeue(batch.deposits().to_vec());
+        }
+        for program in programs.into_iter().rev() {
+            self.receipts.program_pending(program.id().to_string());
+            self.pending_programs.push_front(program);
+        }
+        for group in groups.into_iter().rev() {
+            for transaction in &group {
+                self.receipts.pending(transaction.hash());
+                if let Err(e) = self.log(WalRecord::Admitted(transaction.clone())) {
+                    error!("Failed to record requeued transaction: {}", e);
+                }
+            }
+            self.retry_groups.push_front(group);
+        }
+
+        self.sealed_state = self.state.clone();
+        self.sealed_head = self.headers.values().next_back().cloned();
+        self.pending_transactions.resync(&self.state);
+    }
+
+    pub fn open_wal(&mut self, path: impl AsRef<Path>) -> Result<Vec<SealedBatch>, HVMError> {
+        let (wal, records) = WriteAheadLog::open(path)?;
+        self.wal = None;
+
+        let mut admitted: Vec<Transaction> = Vec::new();
+        let mut sealed = VecDeque::new();
+        for record in records {
+            match record {
+                WalRecord::Admitted(transaction) => admitted.push(transaction),
+                WalRecord::Deployed(program) => {
+                    self.deployed_programs.insert(program.id().to_string(), program);
+                }
+                WalRecord::Forced(forced, deadline) => {
+                    self.forced.advance_cursor(forced.id());
+                    self.receipts.pending(forced.transaction.hash());
+                    self.forced.push(forced, deadline);
+                }
+                WalRecord::Sealed(batch) => {
+                    for deposit in batch.deposits() {
+                        self.pending_deposits.advance_cursor(deposit.id());
+                    }
+                    self.pending_deposits.remove(batch.deposits());
+                    let included: HashSet<merkle::Hash> = batch.transactions().iter().map(Transaction::hash).collect();
+                    admitted.retain(|transaction| !included.contains(&transaction.hash()));
+                    sealed.push_back(batch);
+                }
+                WalRecord::Committed(batch_id) => {
+                    let batch = sealed
+                        .pop_front()
+                        .filter(|batch: &Batch| batch.batch_id() == batch_id)
+                        .ok_or_else(|| HVMError::Sequencer(format!("WAL commits batch {} out of order", merkle::to_hex(&batch_id))))?;
+                    self.execute_batch(&batch)?;
+                }
+                WalRecord::Abandoned => {
+                    for batch in sealed.drain(..).rev() {
+                        self.pending_deposits.requeue(batch.deposits().to_vec());
+                    }
+                }
+                WalRecord::Reverted(batch_id) => {
+                    self.revert_batch(&batch_id)?;
+                }
+            }
+        }
+
+        self.abandon_sealed();
+        let mut backlog = Vec::new();
+        for batch in sealed {
+            let pre_state = self.sealed_state.clone();
+            if batch.header().pre_state_root != pre_state.root() {
+                return Err(HVMError::Sequencer(format!("WAL batch {} does not extend the restored state", merkle::to_hex(&batch.batch_id()))));
+            }
+            let transition = transition::apply_batch(&pre_state, &batch, &self.deployed_programs)?;
+            self.sealed_accounts.insert(batch.height(), transition.touched_accounts());
+            self.sealed_state = transition.post_state;
+            self.sealed_head = Some(batch.header().clone());
+            self.receipts.batched(&batch);
+            self.in_flight.insert(batch.height(), batch.clone());
+            self.forced.mark_sealed(batch.height(), &batch.transactions().iter().map(Transaction::hash).collect());
+            backlog.push(SealedBatch { batch, pre_state });
+        }
+        for transaction in admitted {
+            let account_nonce = self.sealed_state.get_account(&transaction.sender).map_or(0, |account| account.nonce);
+            let hash = transaction.hash();
+            if self.pending_transactions.insert(transaction, account_nonce).is_ok() {
+                self.receipts.pending(hash);
+            }
+        }
+
+        self.wal = Some(wal);
+        Ok(backlog)
+    }
+
+    fn log(&mut self, record: WalRecord) -> Result<(), HVMError> {
+        match self.wal.as_mut() {
+            Some(wal) => wal.append(&record),
+            None => Ok(()),
+        }
+    }
+
+    pub fn rollback_to_batch(&mut self, batch_id: &merkle::Hash) -> Result<Vec<merkle::Hash>, HVMError> {
+        let reverted = self.snapshots.rollback_to(&mut self.state, batch_id)?;
+        self.log_reverted(&reverted)?;
+        Ok(self.discard_reverted(reverted))
+    }
+
+    pub fn revert_batch(&mut self, batch_id: &merkle::Hash) -> Result<Vec<merkle::Hash>, HVMError> {
+        let reverted = self.snapshots.revert(&mut self.state, batch_id)?;
+        self.log_reverted(&reverted)?;
+        Ok(self.discard_reverted(reverted))
+    }
+
+    fn log_reverted(&mut self, reverted: &[StateSnapshot]) -> Result<(), HVMError> {
+        match reverted.first() {
+            Some(first) => self.log(WalRecord::Reverted(first.batch_id)),
+            None => Ok(()),
+        }
+    }
+
+    fn discard_reverted(&mut self, reverted: Vec<StateSnapshot>) -> Vec<merkle::Hash> {
+        if let Some(first) = reverted.first() {
+            self.processed_transactions.truncate(first.processed_transactions);
+            for snapshot in reverted.iter().rev() {
+                self.withdrawals.remove(&snapshot.batch_id);
+                self.rejected_deposits.remove(&snapshot.batch_id);
+                self.collected_fees -= self.batch_fees.remove(&snapshot.batch_id).unwrap_or(0);
+                if let Some(deposits) = self.included_deposits.remove(&snapshot.batch_id) {
+                    self.pending_deposits.requeue(deposits);
+                }
+                if let Some(forced) = self.included_forced.remove(&snapshot.batch_id) {
+                    self.forced.requeue(forced);
+                }
+                for program_id in self.program_batches.remove(&snapshot.batch_id).unwrap_or_default() {
+                    self.deployed_programs.remove(&program_id);
+                    self.processed_programs.retain(|program| program.id() != program_id);
+                }
+            }
+            for header in self.headers.split_off(&first.height).into_values() {
+                self.batch_heights.remove(&header.hash());
+            }
+            self.receipts.discard_from(first.height, "reverted");
+            self.abandon_sealed();
+            self.pending_transactions.resync(&self.state);
+            info!("Rolled back to state root {}", merkle::to_hex(&self.state.root()));
+        }
+        reverted.iter().map(|snapshot| snapshot.batch_id).collect()
+    }
+
+    pub fn withdrawals(&self, batch_id: &merkle::Hash) -> &[Withdrawal] {
+        self.withdrawals.get(batch_id).map_or(&[], Vec::as_slice)
+    }
+
+    pub fn rejected_deposits(&self, batch_id: &merkle::Hash) -> &[DepositId] {
+        self.rejected_deposits.get(batch_id).map_or(&[], Vec::as_slice)
+    }
+
+    pub fn generate_withdrawal_claim(&self, batch_id: &merkle::Hash, index: u32) -> Result<WithdrawalClaim, HVMError> {
+        withdrawal::generate_claim(self.withdrawals(batch_id), index)
+            .map_err(|_| HVMError::Sequencer(format!("Withdrawal {} not found in batch {}", index, merkle::to_hex(batch_id))))
+    }
+
+    pub fn state_diff(&self, batch_id: &merkle::Hash) -> Option<&StateDiff> {
+        self.snapshots.get(batch_id).map(StateSnapshot::state_diff)
+    }
+
+    pub fn head(&self) -> Option<&BatchHeader> {
+        self.headers.values().next_back()
+    }
+
+    fn next_height(&self) -> u64 {
+        self.head().map_or(0, |header| header.height + 1)
+    }
+
+    pub fn receipt(&self, hash: &merkle::Hash) -> Option<&TransactionReceipt> {
+        self.receipts.get(hash)
+    }
+
+    pub fn batch_receipts(&self, batch_id: &merkle::Hash) -> Vec<&TransactionReceipt> {
+        self.receipts.batch(batch_id)
+    }
+
+    pub fn deployment_receipt(&self, program_id: &str) -> Option<&DeploymentReceipt> {
+        self.receipts.deployment(program_id)
+    }
+
+    pub fn batch_deployments(&self, batch_id: &merkle::Hash) -> Vec<&DeploymentReceipt> {
+        self.receipts.batch_deployments(batch_id)
+    }
+
+    pub fn mark_batch_submitted(&mut self, batch_id: &merkle::Hash) -> Result<(), HVMError> {
+        self.receipts.submitted(batch_id)
+    }
+
+    pub fn mark_batch_finalized(&mut self, batch_id: &merkle::Hash) -> Result<(), HVMError> {
+        self.receipts.finalized(batch_id)
+    }
+
+    pub fn batch_header(&self, batch_id: &merkle::Hash) -> Option<&BatchHeader> {
+        self.batch_heights.get(batch_id).and_then(|height| self.headers.get(height))
+    }
+
+    pub fn snapshots(&self) -> &SnapshotJournal {
+        &self.snapshots
     }
 
     pub fn execute_program(&self, program_id: &str, inputs: Vec<u8>) -> Result<Vec<u8>, HVMError> {
         let program = self.deployed_programs.get(program_id)
             .ok_or_else(|| HVMError::Sequencer(format!("Program not found: {}", program_id)))?;
         
-        let result = program.execute(inputs)?;
-        Ok(result
-            .iter()
-            .flat_map(|fr| {
-                let mut bytes = Vec::new();
-                fr.serialize_uncompressed(&mut bytes)
-                    .unwrap_or_else(|_| bytes.clear());
-                bytes
-            })
-            .collect())
+        let result = program.execute(inputs, transition::PROGRAM_GAS_LIMIT)?;
+        Ok(bend::encode_outputs(&result))
     }
 
     pub fn get_current_state(&self) -> State {
         self.state.clone()
     }
 
+    pub fn state(&self) -> &State {
+        &self.state
+    }
+
+    pub fn sealed_state(&self) -> &State {
+        &self.sealed_state
+    }
+
+    pub fn config(&self) -> &SequencerConfig {
+        &self.config
+    }
+
+    pub fn is_batch_full(&self) -> bool {
+        let forced = self.forced.unsealed().count();
+        if forced + self.pending_deposits.len() + self.pending_transactions.ready_len() >= self.config.max_batch_size {
+            return true;
+        }
+        let pending: BatchWeight = self
+            .pending_deposits
+            .pending()
+            .iter()
+            .map(BatchWeight::of_deposit)
+            .chain(self.forced.unsealed().map(|entry| BatchWeight::of_transaction(&entry.forced.transaction)))
+            .chain(self.pending_transactions.ready_transactions().map(BatchWeight::of_transaction))
+            .sum();
+        !(BatchWeight::empty(&self.config) + pending).fits(&self.config)
+    }
+
+    pub fn predict_proving_time(&self) -> Duration {
+        BatchWeight::proving_time(&self.config)
+    }
+
+    pub fn predict_post_size(&self, batch: &Batch) -> usize {
+        BatchWeight::of_batch(batch).bytes
+    }
+
+    pub fn has_pending_work(&self) -> bool {
+        !self.retry_groups.is_empty()
+            || self.forced.unsealed().next().is_some()
+            || !self.pending_deposits.is_empty()
+            || self.pending_transactions.ready_len() > 0
+            || !self.pending_programs.is_empty()
+    }
+
     pub fn pending_transactions_count(&self) -> usize {
         self.pending_transactions.len()
     }
 
+    pub fn collected_fees(&self) -> u128 {
+        self.collected_fees
+    }
+
+    pub fn pending_deposits_count(&self) -> usize {
+        self.pending_deposits.len()
+    }
+
     pub fn pending_programs_count(&self) -> usize {
         self.pending_programs.len()
     }
 
+    pub fn forced_transactions_count(&self) -> usize {
+        self.forced.len()
+    }
+
     pub fn processed_transactions_count(&self) -> usize {
         self.processed_transactions.len()
     }
@@ -139,8 +826,20 @@ impl Sequencer {
         self.processed_programs.len()
     }
 
-    pub fn get_pending_transactions(&self) -> &VecDeque<Transaction> {
-        &self.pending_transactions
+    pub fn ready_transactions_count(&self) -> usize {
+        self.pending_transactions.ready_len()
+    }
+
+    pub fn future_transactions_count(&self) -> usize {
+        self.pending_transactions.future_len()
+    }
+
+    pub fn get_pending_transactions(&self) -> Vec<&Transaction> {
+        self.pending_transactions.transactions()
+    }
+
+    pub fn deployed_programs(&self) -> &HashMap<String, BendProgram> {
+        &self.deployed_programs
     }
 
     pub fn get_pending_programs(&self) -> &VecDeque<BendProgram> {
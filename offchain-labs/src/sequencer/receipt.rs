@@ -0,0 +1,245 @@
+use super::batch::Batch;
+use super::transaction::Transaction;
+use crate::bend::BendProgram;
+use crate::error::HVMError;
+use crate::zk_rollup::merkle::{self, Hash};
+use crate::zk_rollup::{transition, TransactionOutcome};
+use serde::{Serialize, Deserialize};
+use std::collections::{BTreeMap, HashMap};
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TransactionStatus {
+    Pending,
+    Batched,
+    Proven,
+    Submitted,
+    Finalized,
+    Failed(String),
+}
+
+impl TransactionStatus {
+    pub fn is_final(&self) -> bool {
+        matches!(self, TransactionStatus::Finalized | TransactionStatus::Failed(_))
+    }
+
+    pub fn is_proven(&self) -> bool {
+        matches!(self, TransactionStatus::Proven | TransactionStatus::Submitted | TransactionStatus::Finalized)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TransactionReceipt {
+    #[serde(with = "hex::serde")]
+    pub transaction_hash: Hash,
+    pub status: TransactionStatus,
+    #[serde(with = "crate::zk_rollup::encoding::hex_option")]
+    pub batch_id: Option<Hash>,
+    pub output: Vec<u8>,
+    pub gas_used: u64,
+    pub logs: Vec<String>,
+}
+
+impl TransactionReceipt {
+    fn pending(transaction_hash: Hash) -> Self {
+        Self {
+            transaction_hash,
+            status: TransactionStatus::Pending,
+            batch_id: None,
+            output: Vec::new(),
+            gas_used: 0,
+            logs: Vec::new(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeploymentReceipt {
+    pub program_id: String,
+    pub status: TransactionStatus,
+    #[serde(with = "crate::zk_rollup::encoding::hex_option")]
+    pub batch_id: Option<Hash>,
+}
+
+impl DeploymentReceipt {
+    fn pending(program_id: String) -> Self {
+        Self { program_id, status: TransactionStatus::Pending, batch_id: None }
+    }
+}
+
+struct BatchReceipts {
+    batch_id: Hash,
+    hashes: Vec<Hash>,
+    program_ids: Vec<String>,
+}
+
+#[derive(Default)]
+pub struct ReceiptStore {
+    receipts: HashMap<Hash, TransactionReceipt>,
+    deployments: HashMap<String, DeploymentReceipt>,
+    batches: BTreeMap<u64, BatchReceipts>,
+    heights: HashMap<Hash, u64>,
+}
+
+impl ReceiptStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, hash: &Hash) -> Option<&TransactionReceipt> {
+        self.receipts.get(hash)
+    }
+
+    pub fn deployment(&self, program_id: &str) -> Option<&DeploymentReceipt> {
+        self.deployments.get(program_id)
+    }
+
+    pub fn batch(&self, batch_id: &Hash) -> Vec<&TransactionReceipt> {
+        self.entry(batch_id).map_or_else(Vec::new, |entry| {
+            entry.hashes.iter().filter_map(|hash| self.receipts.get(hash)).collect()
+        })
+    }
+
+    pub fn batch_deployments(&self, batch_id: &Hash) -> Vec<&DeploymentReceipt> {
+        self.entry(batch_id).map_or_else(Vec::new, |entry| {
+            entry.program_ids.iter().filter_map(|program_id| self.deployments.get(program_id)).collect()
+        })
+    }
+
+    fn entry(&self, batch_id: &Hash) -> Option<&BatchReceipts> {
+        self.heights.get(batch_id).and_then(|height| self.batches.get(height))
+    }
+
+    fn record(&mut self, batch: &Batch, hashes: Vec<Hash>, program_ids: Vec<String>) {
+        let batch_id = batch.batch_id();
+        if let Some(replaced) = self.batches.insert(batch.height(), BatchReceipts { batch_id, hashes, program_ids }) {
+            self.heights.remove(&replaced.batch_id);
+        }
+        self.heights.insert(batch_id, batch.height());
+    }
+
+    pub fn len(&self) -> usize {
+        self.receipts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.receipts.is_empty()
+    }
+
+    pub fn pending(&mut self, hash: Hash) {
+        self.receipts.insert(hash, TransactionReceipt::pending(hash));
+    }
+
+    pub fn program_pending(&mut self, program_id: String) {
+        self.deployments.insert(program_id.clone(), DeploymentReceipt::pending(program_id));
+    }
+
+    pub fn batched(&mut self, batch: &Batch) {
+        let batch_id = batch.batch_id();
+        let hashes: Vec<Hash> = batch.transactions().iter().map(Transaction::hash).collect();
+        for hash in &hashes {
+            let receipt = self.receipts.entry(*hash).or_insert_with(|| TransactionReceipt::pending(*hash));
+            receipt.status = TransactionStatus::Batched;
+            receipt.batch_id = Some(batch_id);
+        }
+        let program_ids = self.deploy(batch_id, batch.programs().iter(), TransactionStatus::Batched);
+        self.record(batch, hashes, program_ids);
+    }
+
+    pub fn proven(&mut self, batch: &Batch, outcomes: &[TransactionOutcome]) {
+        let batch_id = batch.batch_id();
+        let hashes: Vec<Hash> = batch.transactions().iter().map(Transaction::hash).collect();
+        for (hash, outcome) in hashes.iter().zip(outcomes) {
+            let receipt = self.receipts.entry(*hash).or_insert_with(|| TransactionReceipt::pending(*hash));
+            receipt.status = match &outcome.error {
+                Some(reason) if !outcome.success => TransactionStatus::Failed(reason.clone()),
+                _ => TransactionStatus::Proven,
+            };
+            receipt.batch_id = Some(batch_id);
+            receipt.output = outcome.output.clone();
+            receipt.gas_used = outcome.gas_used;
+            receipt.logs = outcome.logs.clone();
+        }
+        let deployed = transition::collect_deployments(batch, outcomes);
+        let program_ids = self.deploy(batch_id, deployed.into_iter(), TransactionStatus::Proven);
+        self.record(batch, hashes, program_ids);
+    }
+
+    fn deploy<'a>(&mut self, batch_id: Hash, programs: impl Iterator<Item = &'a BendProgram>, status: TransactionStatus) -> Vec<String> {
+        let program_ids: Vec<String> = programs.map(|program| program.id().to_string()).collect();
+        for program_id in &program_ids {
+            let receipt = self
+                .deployments
+                .entry(program_id.clone())
+                .or_insert_with(|| DeploymentReceipt::pending(program_id.clone()));
+            receipt.status = status.clone();
+            receipt.batch_id = Some(batch_id);
+        }
+        program_ids
+    }
+
+    pub fn fail(&mut self, hash: &Hash, reason: String) {
+        if let Some(receipt) = self.receipts.get_mut(hash) {
+            receipt.status = TransactionStatus::Failed(reason);
+        }
+    }
+
+    pub fn fail_deployment(&mut self, program_id: &str, reason: String) {
+        if let Some(receipt) = self.deployments.get_mut(program_id) {
+            receipt.status = TransactionStatus::Failed(reason);
+        }
+    }
+
+    pub fn submitted(&mut self, batch_id: &Hash) -> Result<(), HVMError> {
+        self.advance(batch_id, TransactionStatus::Proven, TransactionStatus::Submitted)
+    }
+
+    pub fn finalized(&mut self, batch_id: &Hash) -> Result<(), HVMError> {
+        self.advance(batch_id, TransactionStatus::Submitted, TransactionStatus::Finalized)
+    }
+
+    pub fn discard_from(&mut self, height: u64, reason: &str) {
+        for discarded in self.batches.split_off(&height).into_values() {
+            self.heights.remove(&discarded.batch_id);
+            let message = format!("Batch {} was {}", merkle::to_hex(&discarded.batch_id), reason);
+            for hash in discarded.hashes {
+                self.fail(&hash, message.clone());
+            }
+            for program_id in discarded.program_ids {
+                self.fail_deployment(&program_id, message.clone());
+            }
+        }
+    }
+
+    fn advance(&mut self, batch_id: &Hash, from: TransactionStatus, to: TransactionStatus) -> Result<(), HVMError> {
+        let BatchReceipts { hashes, program_ids, .. } = self
+            .heights
+            .get(batch_id)
+            .and_then(|height| self.batches.get(height))
+            .ok_or_else(|| HVMError::Sequencer(format!("No receipts recorded for batch {}", merkle::to_hex(batch_id))))?;
+        let blocked = hashes
+            .iter()
+            .filter_map(|hash| self.receipts.get(hash).map(|receipt| (hex::encode(hash), &receipt.status)))
+            .chain(program_ids.iter().filter_map(|id| self.deployments.get(id).map(|receipt| (id.clone(), &receipt.status))))
+            .find(|(_, status)| !status.is_final() && **status != from);
+        if let Some((id, status)) = blocked {
+            return Err(HVMError::Sequencer(format!(
+                "Batch {} cannot move to {:?}: {} is {:?}",
+                merkle::to_hex(batch_id),
+                to,
+                id,
+                status
+            )));
+        }
+        for hash in hashes {
+            if let Some(receipt) = self.receipts.get_mut(hash).filter(|receipt| receipt.status == from) {
+                receipt.status = to.clone();
+            }
+        }
+        for program_id in program_ids {
+            if let Some(receipt) = self.deployments.get_mut(program_id).filter(|receipt| receipt.status == from) {
+                receipt.status = to.clone();
+            }
+        }
+        Ok(())
+    }
+}
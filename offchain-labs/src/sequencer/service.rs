@@ -0,0 +1,273 @@
+use super::{Batch, L1Deposit, Sequencer, Transaction, TransactionReceipt};
+use crate::bend::BendProgram;
+use crate::error::HVMError;
+use crate::zk_rollup::merkle::{self, Hash};
+use crate::zk_rollup::{transition, Proof, State, TransactionOutcome, ZKRollup};
+use log::{error, info};
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::JoinHandle;
+use tokio::time::{self, MissedTickBehavior};
+
+pub struct SealedBatch {
+    pub batch: Batch,
+    pub pre_state: State,
+}
+
+pub struct ProvenBatch {
+    pub proof: Proof,
+    pub post_state: State,
+    pub outcomes: Vec<TransactionOutcome>,
+}
+
+pub trait ProvingStage: Send + 'static {
+    fn prove(&mut self, sealed: &SealedBatch) -> Result<ProvenBatch, HVMError>;
+
+    fn register_programs(&mut self, _programs: Vec<BendProgram>) {}
+}
+
+impl<R: ZKRollup + Send + 'static> ProvingStage for R {
+    fn prove(&mut self, sealed: &SealedBatch) -> Result<ProvenBatch, HVMError> {
+        let proof = self.generate_proof(&sealed.pre_state, &sealed.batch)?;
+        if !self.verify_proof(&proof)? {
+            return Err(HVMError::Verifier(format!("Proof for batch {} failed verification", merkle::to_hex(&sealed.batch.batch_id()))));
+        }
+        let mut post_state = sealed.pre_state.clone();
+        let outcomes = self.apply_proof(&mut post_state, &proof, &sealed.batch)?;
+        Ok(ProvenBatch { proof, post_state, outcomes })
+    }
+
+    fn register_programs(&mut self, programs: Vec<BendProgram>) {
+        for program in programs {
+            self.register_program(program);
+        }
+    }
+}
+
+enum Work {
+    Prove(Box<SealedBatch>),
+    Register(Vec<BendProgram>),
+}
+
+enum Command {
+    Submit { transaction: Transaction, reply: oneshot::Sender<Result<(), HVMError>> },
+    Deposit { deposit: L1Deposit, reply: oneshot::Sender<bool> },
+    Seal { reply: oneshot::Sender<Result<Option<Hash>, HVMError>> },
+    Receipt { hash: Hash, reply: oneshot::Sender<Option<TransactionReceipt>> },
+    Shutdown,
+}
+
+type ProvingResult = (SealedBatch, Result<ProvenBatch, HVMError>);
+
+#[derive(Clone)]
+pub struct SequencerHandle {
+    commands: mpsc::Sender<Command>,
+}
+
+impl SequencerHandle {
+    pub async fn submit(&self, transaction: Transaction) -> Result<(), HVMError> {
+        let (reply, response) = oneshot::channel();
+        self.send(Command::Submit { transaction, reply }).await?;
+        response.await.map_err(|_| stopped())?
+    }
+
+    pub async fn submit_deposit(&self, deposit: L1Deposit) -> Result<bool, HVMError> {
+        let (reply, response) = oneshot::channel();
+        self.send(Command::Deposit { deposit, reply }).await?;
+        response.await.map_err(|_| stopped())
+    }
+
+    pub async fn seal(&self) -> Result<Option<Hash>, HVMError> {
+        let (reply, response) = oneshot::channel();
+        self.send(Command::Seal { reply }).await?;
+        response.await.map_err(|_| stopped())?
+    }
+
+    pub async fn receipt(&self, hash: Hash) -> Result<Option<TransactionReceipt>, HVMError> {
+        let (reply, response) = oneshot::channel();
+        self.send(Command::Receipt { hash, reply }).await?;
+        response.await.map_err(|_| stopped())
+    }
+
+    async fn send(&self, command: Command) -> Result<(), HVMError> {
+        self.commands.send(command).await.map_err(|_| stopped())
+    }
+}
+
+pub struct SequencerService {
+    handle: SequencerHandle,
+    task: JoinHandle<Sequencer>,
+}
+
+impl SequencerService {
+    pub fn spawn<P: ProvingStage>(sequencer: Sequencer, stage: P) -> Self {
+        Self::resume(sequencer, stage, Vec::new())
+    }
+
+    pub fn resume<P: ProvingStage>(sequencer: Sequencer, stage: P, backlog: Vec<SealedBatch>) -> Self {
+        let (commands, receiver) = mpsc::channel(sequencer.config().max_pending_transactions.max(1));
+        let task = tokio::spawn(run(sequencer, receiver, stage, backlog));
+        Self { handle: SequencerHandle { commands }, task }
+    }
+
+    pub fn handle(&self) -> SequencerHandle {
+        self.handle.clone()
+    }
+
+    pub async fn shutdown(self) -> Result<Sequencer, HVMError> {
+        let _ = self.handle.commands.send(Command::Shutdown).await;
+        self.task
+            .await
+            .map_err(|e| HVMError::Sequencer(format!("Sequencer service panicked: {}", e)))
+    }
+}
+
+async fn run<P: ProvingStage>(
+    mut sequencer: Sequencer,
+    mut commands: mpsc::Receiver<Command>,
+    stage: P,
+    backlog: Vec<SealedBatch>,
+) -> Sequencer {
+    let (batches, pending) = mpsc::unbounded_channel();
+    for sealed in backlog {
+        let _ = batches.send(Work::Prove(Box::new(sealed)));
+    }
+    let (results, mut proven) = mpsc::unbounded_channel();
+    let prover = tokio::task::spawn_blocking(move || proving_loop(stage, pending, results));
+
+    let mut ticker = time::interval(Duration::from_secs(sequencer.config().batch_interval_seconds.max(1)));
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    ticker.tick().await;
+
+    loop {
+        tokio::select! {
+            biased;
+            _ = ticker.tick() => {
+                if let Err(e) = seal(&mut sequencer, &batches) {
+                    error!("Failed to seal batch: {}", e);
+                }
+            }
+            Some((sealed, result)) = proven.recv() => register(&batches, finish(&mut sequencer, sealed, result)),
+            command = commands.recv() => match command {
+                Some(Command::Submit { transaction, reply }) => {
+                    let _ = reply.send(sequencer.process_transaction(transaction));
+                    seal_full_batches(&mut sequencer, &batches);
+                }
+                Some(Command::Deposit { deposit, reply }) => {
+                    let _ = reply.send(sequencer.submit_deposit(deposit));
+                    seal_full_batches(&mut sequencer, &batches);
+                }
+                Some(Command::Seal { reply }) => {
+                    let _ = reply.send(seal(&mut sequencer, &batches));
+                }
+                Some(Command::Receipt { hash, reply }) => {
+                    let _ = reply.send(sequencer.receipt(&hash).cloned());
+                }
+                Some(Command::Shutdown) | None => break,
+            },
+        }
+    }
+
+    while sequencer.has_pending_work() {
+        match seal(&mut sequencer, &batches) {
+            Ok(Some(_)) => {}
+            Ok(None) => break,
+            Err(e) => {
+                error!("Failed to seal batch during shutdown: {}", e);
+                break;
+            }
+        }
+    }
+    while sequencer.in_flight_count() > 0 {
+        let Some((sealed, result)) = proven.recv().await else {
+            break;
+        };
+        register(&batches, finish(&mut sequencer, sealed, result));
+    }
+    drop(batches);
+    while let Some((sealed, result)) = proven.recv().await {
+        finish(&mut sequencer, sealed, result);
+    }
+    if let Err(e) = prover.await {
+        error!("Proving stage panicked: {}", e);
+    }
+    info!("Sequencer service stopped");
+    sequencer
+}
+
+fn proving_loop<P: ProvingStage>(
+    mut stage: P,
+    mut pending: mpsc::UnboundedReceiver<Work>,
+    results: mpsc::UnboundedSender<ProvingResult>,
+) {
+    while let Some(work) = pending.blocking_recv() {
+        match work {
+            Work::Prove(sealed) => {
+                let result = stage.prove(&sealed);
+                if results.send((*sealed, result)).is_err() {
+                    break;
+                }
+            }
+            Work::Register(programs) => stage.register_programs(programs),
+        }
+    }
+}
+
+fn seal(sequencer: &mut Sequencer, batches: &mpsc::UnboundedSender<Work>) -> Result<Option<Hash>, HVMError> {
+    let pre_state = sequencer.sealed_state().clone();
+    let Some(batch) = sequencer.create_batch(true)? else {
+        return Ok(None);
+    };
+    let batch_id = batch.batch_id();
+    batches
+        .send(Work::Prove(Box::new(SealedBatch { batch, pre_state })))
+        .map_err(|_| HVMError::Sequencer("Proving stage has stopped".to_string()))?;
+    Ok(Some(batch_id))
+}
+
+fn seal_full_batches(sequencer: &mut Sequencer, batches: &mpsc::UnboundedSender<Work>) {
+    while sequencer.is_batch_full() {
+        match seal(sequencer, batches) {
+            Ok(Some(_)) => {}
+            Ok(None) => break,
+            Err(e) => {
+                error!("Failed to seal full batch: {}", e);
+                break;
+            }
+        }
+    }
+}
+
+fn finish(sequencer: &mut Sequencer, sealed: SealedBatch, result: Result<ProvenBatch, HVMError>) -> Vec<BendProgram> {
+    let batch_id = sealed.batch.batch_id();
+    let batch_hex = merkle::to_hex(&batch_id);
+    if !sequencer.is_in_flight(&sealed.batch) {
+        info!("Ignoring stale result for batch {}", batch_hex);
+        return Vec::new();
+    }
+    let committed = result.and_then(|proven| {
+        sequencer.commit_batch(proven.post_state, &sealed.batch, &proven.outcomes)?;
+        Ok(transition::collect_deployments(&sealed.batch, &proven.outcomes).into_iter().cloned().collect())
+    });
+    match committed {
+        Ok(programs) => {
+            info!("Committed batch {}", batch_hex);
+            programs
+        }
+        Err(e) => {
+            error!("Failed to prove batch {}: {}", batch_hex, e);
+            sequencer.handle_proving_failure(&batch_id, &e.to_string());
+            Vec::new()
+        }
+    }
+}
+
+fn register(batches: &mpsc::UnboundedSender<Work>, programs: Vec<BendProgram>) {
+    if !programs.is_empty() {
+        let _ = batches.send(Work::Register(programs));
+    }
+}
+
+fn stopped() -> HVMError {
+    HVMError::Sequencer("Sequencer service has stopped".to_string())
+}
\ No newline at end of file
@@ -0,0 +1,122 @@
+use crate::error::HVMError;
+use ed25519_dalek::{Signer, Verifier};
+use serde::{Serialize, Deserialize};
+
+const SR25519_SIGNING_CONTEXT: &[u8] = b"substrate";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SignatureScheme {
+    Sr25519,
+    Ed25519,
+}
+
+impl SignatureScheme {
+    pub fn id(&self) -> u8 {
+        match self {
+            SignatureScheme::Sr25519 => 0,
+            SignatureScheme::Ed25519 => 1,
+        }
+    }
+
+    pub fn from_id(id: u8) -> Result<Self, HVMError> {
+        match id {
+            0 => Ok(SignatureScheme::Sr25519),
+            1 => Ok(SignatureScheme::Ed25519),
+            _ => Err(HVMError::InvalidSignature(format!("Unknown signature scheme id: {}", id))),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransactionSignature {
+    pub scheme: SignatureScheme,
+    #[serde(with = "hex::serde")]
+    pub public_key: [u8; 32],
+    #[serde(with = "hex::serde")]
+    pub signature: Vec<u8>,
+}
+
+impl TransactionSignature {
+    pub fn address(&self) -> String {
+        address_from_public_key(&self.public_key)
+    }
+
+    pub fn verify(&self, payload: &[u8]) -> Result<(), HVMError> {
+        match self.scheme {
+            SignatureScheme::Sr25519 => {
+                let public_key = schnorrkel::PublicKey::from_bytes(&self.public_key)
+                    .map_err(|e| HVMError::InvalidSignature(format!("Invalid sr25519 public key: {}", e)))?;
+                let signature = schnorrkel::Signature::from_bytes(&self.signature)
+                    .map_err(|e| HVMError::InvalidSignature(format!("Malformed sr25519 signature: {}", e)))?;
+                public_key
+                    .verify_simple(SR25519_SIGNING_CONTEXT, payload, &signature)
+                    .map_err(|_| HVMError::InvalidSignature("sr25519 signature does not match payload".to_string()))
+            }
+            SignatureScheme::Ed25519 => {
+                let public_key = ed25519_dalek::VerifyingKey::from_bytes(&self.public_key)
+                    .map_err(|e| HVMError::InvalidSignature(format!("Invalid ed25519 public key: {}", e)))?;
+                let signature = ed25519_dalek::Signature::from_slice(&self.signature)
+                    .map_err(|e| HVMError::InvalidSignature(format!("Malformed ed25519 signature: {}", e)))?;
+                public_key
+                    .verify(payload, &signature)
+                    .map_err(|_| HVMError::InvalidSignature("ed25519 signature does not match payload".to_string()))
+            }
+        }
+    }
+}
+
+pub enum TransactionSigner {
+    Sr25519(schnorrkel::Keypair),
+    Ed25519(ed25519_dalek::SigningKey),
+}
+
+impl TransactionSigner {
+    pub fn sr25519_from_seed(seed: &[u8; 32]) -> Result<Self, HVMError> {
+        let secret = schnorrkel::MiniSecretKey::from_bytes(seed)
+            .map_err(|e| HVMError::InvalidSignature(format!("Invalid sr25519 seed: {}", e)))?;
+        Ok(TransactionSigner::Sr25519(secret.expand_to_keypair(schnorrkel::ExpansionMode::Ed25519)))
+    }
+
+    pub fn ed25519_from_seed(seed: &[u8; 32]) -> Self {
+        TransactionSigner::Ed25519(ed25519_dalek::SigningKey::from_bytes(seed))
+    }
+
+    pub fn scheme(&self) -> SignatureScheme {
+        match self {
+            TransactionSigner::Sr25519(_) => SignatureScheme::Sr25519,
+            TransactionSigner::Ed25519(_) => SignatureScheme::Ed25519,
+        }
+    }
+
+    pub fn public_key(&self) -> [u8; 32] {
+        match self {
+            TransactionSigner::Sr25519(keypair) => keypair.public.to_bytes(),
+            TransactionSigner::Ed25519(signing_key) => signing_key.verifying_key().to_bytes(),
+        }
+    }
+
+    pub fn address(&self) -> String {
+        address_from_public_key(&self.public_key())
+    }
+
+    pub fn sign(&self, payload: &[u8]) -> TransactionSignature {
+        let signature = match self {
+            TransactionSigner::Sr25519(keypair) => keypair
+                .sign_simple(SR25519_SIGNING_CONTEXT, payload)
+                .to_bytes()
+                .to_vec(),
+            TransactionSigner::Ed25519(signing_key) => signing_key.sign(payload).to_bytes().to_vec(),
+        };
+
+        TransactionSignature {
+            scheme: self.scheme(),
+            public_key: self.public_key(),
+            signature,
+        }
+    }
+}
+
+pub fn address_from_public_key(public_key: &[u8; 32]) -> String {
+    format!("0x{}", hex::encode(public_key))
+}
\ No newline at end of file
@@ -0,0 +1,110 @@
+use super::batch::BatchHeader;
+use crate::error::HVMError;
+use crate::zk_rollup::merkle::{self, Hash};
+use crate::zk_rollup::{State, StateDiff, TouchedAccounts};
+use std::collections::VecDeque;
+
+#[derive(Clone, Debug)]
+pub struct StateSnapshot {
+    pub batch_id: Hash,
+    pub height: u64,
+    pub pre_root: Hash,
+    pub post_root: Hash,
+    pub processed_transactions: usize,
+    touched: TouchedAccounts,
+    diff: StateDiff,
+}
+
+impl StateSnapshot {
+    pub fn touched_accounts(&self) -> usize {
+        self.touched.len()
+    }
+
+    pub fn state_diff(&self) -> &StateDiff {
+        &self.diff
+    }
+}
+
+pub struct SnapshotJournal {
+    snapshots: VecDeque<StateSnapshot>,
+    max_retained: usize,
+}
+
+impl SnapshotJournal {
+    pub fn new(max_retained: usize) -> Self {
+        Self {
+            snapshots: VecDeque::new(),
+            max_retained,
+        }
+    }
+
+    pub fn record(&mut self, header: &BatchHeader, touched: TouchedAccounts, processed_transactions: usize) {
+        let batch_id = header.hash();
+        self.snapshots.push_back(StateSnapshot {
+            batch_id,
+            height: header.height,
+            pre_root: header.pre_state_root,
+            post_root: header.post_state_root,
+            processed_transactions,
+            diff: StateDiff::new(batch_id, header.pre_state_root, header.post_state_root, &touched),
+            touched,
+        });
+        while self.snapshots.len() > self.max_retained {
+            self.snapshots.pop_front();
+        }
+    }
+
+    pub fn get(&self, batch_id: &Hash) -> Option<&StateSnapshot> {
+        self.snapshots.iter().find(|snapshot| snapshot.batch_id == *batch_id)
+    }
+
+    pub fn contains(&self, batch_id: &Hash) -> bool {
+        self.get(batch_id).is_some()
+    }
+
+    pub fn batch_ids(&self) -> Vec<Hash> {
+        self.snapshots.iter().map(|snapshot| snapshot.batch_id).collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.snapshots.is_empty()
+    }
+
+    pub fn rollback_to(&mut self, state: &mut State, batch_id: &Hash) -> Result<Vec<StateSnapshot>, HVMError> {
+        let position = self.position(batch_id)?;
+        self.undo_from(state, position + 1)
+    }
+
+    pub fn revert(&mut self, state: &mut State, batch_id: &Hash) -> Result<Vec<StateSnapshot>, HVMError> {
+        let position = self.position(batch_id)?;
+        self.undo_from(state, position)
+    }
+
+    fn position(&self, batch_id: &Hash) -> Result<usize, HVMError> {
+        self.snapshots
+            .iter()
+            .position(|snapshot| snapshot.batch_id == *batch_id)
+            .ok_or_else(|| HVMError::Sequencer(format!("No retained snapshot for batch {}", merkle::to_hex(batch_id))))
+    }
+
+    fn undo_from(&mut self, state: &mut State, position: usize) -> Result<Vec<StateSnapshot>, HVMError> {
+        for (undone, snapshot) in self.snapshots.range(position..).rev().enumerate() {
+            if state.root() != snapshot.post_root {
+                for redone in self.snapshots.range(self.snapshots.len() - undone..) {
+                    state.update_accounts(redone.touched.after());
+                }
+                return Err(HVMError::Sequencer(format!(
+                    "State root does not match the snapshot of batch {}",
+                    merkle::to_hex(&snapshot.batch_id)
+                )));
+            }
+            state.update_accounts(snapshot.touched.before());
+        }
+
+        Ok(self.snapshots.drain(position..).collect())
+    }
+}
\ No newline at end of file
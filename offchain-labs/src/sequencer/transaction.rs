@@ -1,16 +1,233 @@
+use super::signing::{SignatureScheme, TransactionSignature, TransactionSigner};
+use crate::bend::{BendProgram, ProgramMetadata};
+use crate::error::HVMError;
+use crate::zk_rollup::encoding::{self, ByteReader};
+use crate::zk_rollup::merkle::Hash;
 use serde::{Serialize, Deserialize};
+use sha2::{Digest, Sha256};
+
+pub const TRANSACTION_FORMAT_VERSION: u8 = 3;
+const SIGNING_DOMAIN: &[u8] = b"hvm-rollup/transaction/v3";
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TransactionKind {
+    Transfer(u128),
+    ProgramCall { program_id: String, calldata: Vec<u8>, value: u128 },
+    DeployProgram(Box<BendProgram>),
+    Withdraw(u128),
+}
+
+impl TransactionKind {
+    pub fn value(&self) -> u128 {
+        match self {
+            TransactionKind::Transfer(amount) | TransactionKind::Withdraw(amount) => *amount,
+            TransactionKind::ProgramCall { value, .. } => *value,
+            TransactionKind::DeployProgram(_) => 0,
+        }
+    }
+
+    fn encode_to(&self, bytes: &mut Vec<u8>) {
+        match self {
+            TransactionKind::Transfer(amount) => {
+                bytes.push(0);
+                bytes.extend_from_slice(&amount.to_le_bytes());
+            }
+            TransactionKind::ProgramCall { program_id, calldata, value } => {
+                bytes.push(1);
+                encoding::put_bytes(bytes, program_id.as_bytes());
+                encoding::put_bytes(bytes, calldata);
+                bytes.extend_from_slice(&value.to_le_bytes());
+            }
+            TransactionKind::DeployProgram(program) => {
+                bytes.push(2);
+                bytes.extend_from_slice(&program.encode());
+            }
+            TransactionKind::Withdraw(amount) => {
+                bytes.push(3);
+                bytes.extend_from_slice(&amount.to_le_bytes());
+            }
+        }
+    }
+
+    fn decode_from(reader: &mut ByteReader) -> Result<Self, HVMError> {
+        match reader.take(1)?[0] {
+            0 => Ok(TransactionKind::Transfer(reader.take_u128()?)),
+            1 => Ok(TransactionKind::ProgramCall {
+                program_id: reader.take_string()?,
+                calldata: reader.take_bytes()?.to_vec(),
+                value: reader.take_u128()?,
+            }),
+            2 => {
+                let bytecode = reader.take_bytes()?.to_vec();
+                let metadata = ProgramMetadata {
+                    name: reader.take_string()?,
+                    version: reader.take_string()?,
+                    description: reader.take_string()?,
+                };
+                Ok(TransactionKind::DeployProgram(Box::new(BendProgram::new(bytecode, metadata, reader.take_string()?))))
+            }
+            3 => Ok(TransactionKind::Withdraw(reader.take_u128()?)),
+            tag => Err(HVMError::Sequencer(format!("Unknown transaction kind: {}", tag))),
+        }
+    }
+}
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Transaction {
+    #[serde(default)]
+    pub chain_id: u64,
     pub sender: String,
     pub recipient: String,
-    pub amount: Vec<u8>,
     pub nonce: u64,
-    pub program_id: String,
+    pub kind: TransactionKind,
+    #[serde(default)]
+    pub max_fee: u128,
+    #[serde(default)]
+    pub priority_tip: u128,
+    #[serde(default)]
+    pub signature: Option<TransactionSignature>,
 }
 
 impl Transaction {
-    pub fn new(sender: String, recipient: String, amount: Vec<u8>, nonce: u64, program_id: String) -> Self {
-        Self { sender, recipient, amount, nonce, program_id }
+    pub fn new(chain_id: u64, sender: String, recipient: String, kind: TransactionKind, nonce: u64) -> Self {
+        Self { chain_id, sender, recipient, nonce, kind, max_fee: 0, priority_tip: 0, signature: None }
+    }
+
+    pub fn transfer(chain_id: u64, sender: String, recipient: String, amount: u128, nonce: u64) -> Self {
+        Self::new(chain_id, sender, recipient, TransactionKind::Transfer(amount), nonce)
+    }
+
+    pub fn call(chain_id: u64, sender: String, recipient: String, program_id: String, calldata: Vec<u8>, value: u128, nonce: u64) -> Self {
+        Self::new(chain_id, sender, recipient, TransactionKind::ProgramCall { program_id, calldata, value }, nonce)
+    }
+
+    pub fn deploy(chain_id: u64, sender: String, program: BendProgram, nonce: u64) -> Self {
+        Self::new(chain_id, sender.clone(), sender, TransactionKind::DeployProgram(Box::new(program)), nonce)
+    }
+
+    pub fn withdrawal(chain_id: u64, sender: String, l1_recipient: String, amount: u128, nonce: u64) -> Self {
+        Self::new(chain_id, sender, l1_recipient, TransactionKind::Withdraw(amount), nonce)
+    }
+
+    pub fn with_chain_id(mut self, chain_id: u64) -> Self {
+        self.chain_id = chain_id;
+        self
+    }
+
+    pub fn with_fee(mut self, max_fee: u128, priority_tip: u128) -> Self {
+        self.max_fee = max_fee;
+        self.priority_tip = priority_tip;
+        self
+    }
+
+    pub fn effective_fee(&self, base_fee: u128) -> Option<u128> {
+        if self.max_fee < base_fee {
+            return None;
+        }
+        Some(self.max_fee.min(base_fee.saturating_add(self.priority_tip)))
+    }
+
+    pub fn value(&self) -> u128 {
+        self.kind.value()
+    }
+
+    pub fn is_withdrawal(&self) -> bool {
+        matches!(self.kind, TransactionKind::Withdraw(_))
+    }
+
+    pub fn deployed_program(&self) -> Option<&BendProgram> {
+        match &self.kind {
+            TransactionKind::DeployProgram(program) => Some(program.as_ref()),
+            _ => None,
+        }
+    }
+
+    pub fn encode_unsigned(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(96 + self.sender.len() + self.recipient.len());
+        bytes.push(TRANSACTION_FORMAT_VERSION);
+        bytes.extend_from_slice(&self.chain_id.to_le_bytes());
+        encoding::put_bytes(&mut bytes, self.sender.as_bytes());
+        encoding::put_bytes(&mut bytes, self.recipient.as_bytes());
+        bytes.extend_from_slice(&self.nonce.to_le_bytes());
+        self.kind.encode_to(&mut bytes);
+        bytes.extend_from_slice(&self.max_fee.to_le_bytes());
+        bytes.extend_from_slice(&self.priority_tip.to_le_bytes());
+        bytes
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = self.encode_unsigned();
+        match &self.signature {
+            Some(signature) => {
+                bytes.push(1);
+                bytes.push(signature.scheme.id());
+                bytes.extend_from_slice(&signature.public_key);
+                encoding::put_bytes(&mut bytes, &signature.signature);
+            }
+            None => bytes.push(0),
+        }
+        bytes
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, HVMError> {
+        let mut reader = ByteReader::new(bytes);
+        let version = reader.take(1)?[0];
+        if version != TRANSACTION_FORMAT_VERSION {
+            return Err(HVMError::Sequencer(format!("Unsupported transaction format version: {}", version)));
+        }
+        let chain_id = reader.take_u64()?;
+        let sender = reader.take_string()?;
+        let recipient = reader.take_string()?;
+        let nonce = reader.take_u64()?;
+        let kind = TransactionKind::decode_from(&mut reader)?;
+        let max_fee = reader.take_u128()?;
+        let priority_tip = reader.take_u128()?;
+        let signature = match reader.take(1)?[0] {
+            0 => None,
+            1 => Some(TransactionSignature {
+                scheme: SignatureScheme::from_id(reader.take(1)?[0])?,
+                public_key: reader.take_hash()?,
+                signature: reader.take_bytes()?.to_vec(),
+            }),
+            flag => return Err(HVMError::Sequencer(format!("Invalid signature flag: {}", flag))),
+        };
+        if !reader.is_empty() {
+            return Err(HVMError::Sequencer("Trailing bytes after transaction".to_string()));
+        }
+
+        Ok(Self { chain_id, sender, recipient, nonce, kind, max_fee, priority_tip, signature })
+    }
+
+    pub fn signing_payload(&self) -> Vec<u8> {
+        let mut payload = SIGNING_DOMAIN.to_vec();
+        payload.extend_from_slice(&self.encode_unsigned());
+        payload
+    }
+
+    pub fn hash(&self) -> Hash {
+        Sha256::digest(self.encode_unsigned()).into()
+    }
+
+    pub fn encoded_size(&self) -> usize {
+        self.encode().len()
+    }
+
+    pub fn sign(mut self, signer: &TransactionSigner) -> Self {
+        self.signature = Some(signer.sign(&self.signing_payload()));
+        self
+    }
+
+    pub fn verify_signature(&self) -> Result<(), HVMError> {
+        let signature = self.signature
+            .as_ref()
+            .ok_or_else(|| HVMError::InvalidSignature(format!("Transaction from {} is not signed", self.sender)))?;
+        if signature.address() != self.sender {
+            return Err(HVMError::InvalidSignature(format!(
+                "Signing key {} does not match sender {}",
+                signature.address(),
+                self.sender
+            )));
+        }
+        signature.verify(&self.signing_payload())
     }
 }
\ No newline at end of file
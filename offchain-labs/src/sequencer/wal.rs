@@ -0,0 +1,107 @@
+use super::batch::Batch;
+use super::forced::ForcedTransaction;
+use super::transaction::Transaction;
+use crate::bend::BendProgram;
+use crate::error::HVMError;
+use crate::zk_rollup::merkle::Hash;
+use log::warn;
+use serde::{Serialize, Deserialize};
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+const RECORD_HEADER_LEN: usize = 12;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum WalRecord {
+    Admitted(Transaction),
+    Deployed(BendProgram),
+    Forced(ForcedTransaction, u64),
+    Sealed(Batch),
+    Committed(Hash),
+    Abandoned,
+    Reverted(Hash),
+}
+
+impl WalRecord {
+    pub fn encode(&self) -> Result<Vec<u8>, HVMError> {
+        let payload = bincode::serialize(self)
+            .map_err(|e| HVMError::Sequencer(format!("Failed to encode WAL record: {}", e)))?;
+        let len = (payload.len() as u32).to_le_bytes();
+        let mut bytes = Vec::with_capacity(RECORD_HEADER_LEN + payload.len());
+        bytes.extend_from_slice(&len);
+        bytes.extend_from_slice(&crc32fast::hash(&len).to_le_bytes());
+        bytes.extend_from_slice(&record_checksum(&len, &payload).to_le_bytes());
+        bytes.extend_from_slice(&payload);
+        Ok(bytes)
+    }
+}
+
+fn record_checksum(len: &[u8], payload: &[u8]) -> u32 {
+    let mut hasher = crc32fast::Hasher::new();
+    hasher.update(len);
+    hasher.update(payload);
+    hasher.finalize()
+}
+
+pub fn decode_records(bytes: &[u8]) -> Result<(Vec<WalRecord>, usize), HVMError> {
+    let mut records = Vec::new();
+    let mut offset = 0;
+    while bytes.len() - offset >= RECORD_HEADER_LEN {
+        let corrupt = || HVMError::Sequencer(format!("Corrupt WAL record at offset {}", offset));
+        let len_bytes = &bytes[offset..offset + 4];
+        let len_checksum = u32::from_le_bytes(bytes[offset + 4..offset + 8].try_into().unwrap());
+        let checksum = u32::from_le_bytes(bytes[offset + 8..offset + 12].try_into().unwrap());
+        let start = offset + RECORD_HEADER_LEN;
+        let end = start.saturating_add(u32::from_le_bytes(len_bytes.try_into().unwrap()) as usize);
+        if end > bytes.len() {
+            if crc32fast::hash(len_bytes) == len_checksum {
+                break;
+            }
+            return Err(corrupt());
+        }
+        let payload = &bytes[start..end];
+        let record = (record_checksum(len_bytes, payload) == checksum)
+            .then(|| bincode::deserialize::<WalRecord>(payload).ok())
+            .flatten();
+        match record {
+            Some(record) => records.push(record),
+            None if end == bytes.len() => break,
+            None => return Err(corrupt()),
+        }
+        offset = end;
+    }
+    Ok((records, offset))
+}
+
+pub struct WriteAheadLog {
+    path: PathBuf,
+    file: File,
+}
+
+impl WriteAheadLog {
+    pub fn open(path: impl AsRef<Path>) -> Result<(Self, Vec<WalRecord>), HVMError> {
+        let path = path.as_ref().to_path_buf();
+        let mut file = OpenOptions::new().read(true).append(true).create(true).open(&path)?;
+        let mut bytes = Vec::new();
+        file.read_to_end(&mut bytes)?;
+
+        let (records, valid_len) = decode_records(&bytes)?;
+        if valid_len < bytes.len() {
+            warn!("Truncating torn WAL record at offset {} in {}", valid_len, path.display());
+            file.set_len(valid_len as u64)?;
+            file.sync_all()?;
+        }
+        Ok((Self { path, file }, records))
+    }
+
+    pub fn append(&mut self, record: &WalRecord) -> Result<(), HVMError> {
+        self.file.write_all(&record.encode()?)?;
+        self.file.sync_data()?;
+        Ok(())
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
\ No newline at end of file
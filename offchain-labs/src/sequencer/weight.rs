@@ -0,0 +1,95 @@
+use super::batch::Batch;
+use super::deposit::L1Deposit;
+use super::transaction::Transaction;
+use crate::bend::BendProgram;
+use crate::config::SequencerConfig;
+use crate::zk_rollup::circuit;
+use crate::zk_rollup::transition::{self, BYTE_GAS};
+use std::iter::Sum;
+use std::ops::{Add, AddAssign};
+use std::time::Duration;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BatchWeight {
+    pub bytes: usize,
+    pub gas: u64,
+    pub transfers: usize,
+}
+
+impl BatchWeight {
+    pub fn empty(config: &SequencerConfig) -> Self {
+        Self::header(&config.fee_recipient)
+    }
+
+    fn header(fee_recipient: &str) -> Self {
+        let empty = Batch::new(Vec::new(), Vec::new()).with_fee_recipient(fee_recipient.to_string());
+        Self { bytes: empty.encoded_size(), gas: 0, transfers: 1 }
+    }
+
+    pub fn of_transaction(transaction: &Transaction) -> Self {
+        Self {
+            bytes: transaction.encoded_size(),
+            gas: transition::intrinsic_gas(transaction),
+            transfers: 1,
+        }
+    }
+
+    pub fn of_deposit(deposit: &L1Deposit) -> Self {
+        Self {
+            bytes: deposit.encode().len(),
+            gas: 0,
+            transfers: 1,
+        }
+    }
+
+    pub fn of_program(program: &BendProgram) -> Self {
+        Self {
+            bytes: program.encode().len(),
+            gas: BYTE_GAS.saturating_mul(program.bytecode.len() as u64),
+            transfers: 0,
+        }
+    }
+
+    pub fn of_batch(batch: &Batch) -> Self {
+        let mut weight = Self::header(batch.fee_recipient());
+        weight += batch.deposits().iter().map(Self::of_deposit).sum();
+        weight += batch.transactions().iter().map(Self::of_transaction).sum();
+        weight += batch.programs().iter().map(Self::of_program).sum();
+        weight
+    }
+
+    pub fn fits(&self, config: &SequencerConfig) -> bool {
+        self.bytes <= config.max_batch_bytes
+            && self.gas <= config.max_batch_gas
+            && self.transfers <= circuit::capacity_for(config.max_batch_size)
+    }
+
+    pub fn proving_time(config: &SequencerConfig) -> Duration {
+        let constraints = circuit::constraints_for(circuit::capacity_for(config.max_batch_size));
+        Duration::from_secs_f64(constraints as f64 / config.proving_constraints_per_second.max(1) as f64)
+    }
+}
+
+impl Add for BatchWeight {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            bytes: self.bytes.saturating_add(other.bytes),
+            gas: self.gas.saturating_add(other.gas),
+            transfers: self.transfers.saturating_add(other.transfers),
+        }
+    }
+}
+
+impl AddAssign for BatchWeight {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+impl Sum for BatchWeight {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::default(), Add::add)
+    }
+}
\ No newline at end of file
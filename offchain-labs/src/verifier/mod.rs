@@ -1,50 +1,54 @@
 use crate::error::HVMError;
-use crate::zk_rollup::Proof;
+use crate::zk_rollup::{Proof, ProofSystem};
+use crate::zk_rollup::merkle::Hash;
+use crate::zk_rollup::proof::{verifying_key_hash, PROOF_FORMAT_VERSION};
 use ark_bn254::{Bn254, Fr};
 use ark_groth16::{Groth16, PreparedVerifyingKey, VerifyingKey};
 use ark_snark::SNARK;
 use ark_serialize::CanonicalDeserialize;
-use ark_ec::AffineRepr;
-use ark_ff::PrimeField;
-use std::ops::AddAssign;
 
 pub struct ZKVerifier {
     verifying_key: PreparedVerifyingKey<Bn254>,
+    vk_hash: Hash,
 }
 
 impl ZKVerifier {
-    pub fn new(verifying_key: VerifyingKey<Bn254>) -> Self {
-        println!("Creating new ZKVerifier with verifying key: {:?}", verifying_key);
-        let prepared_verifying_key = Groth16::<Bn254>::process_vk(&verifying_key).unwrap();
-        Self { verifying_key: prepared_verifying_key }
+    pub fn new(verifying_key: VerifyingKey<Bn254>) -> Result<Self, HVMError> {
+        let vk_hash = verifying_key_hash(&verifying_key)?;
+        let prepared_verifying_key = Groth16::<Bn254>::process_vk(&verifying_key)
+            .map_err(|e| HVMError::Verifier(format!("Failed to prepare verifying key: {}", e)))?;
+        Ok(Self { verifying_key: prepared_verifying_key, vk_hash })
     }
 
-    pub fn verify_proof(&self, proof: &Proof, public_inputs: &[Fr]) -> Result<bool, HVMError> {
-        println!("Public input length: {:?}", public_inputs.len());
-        println!("Verifying with processed key length: {:?}", self.verifying_key.vk.gamma_abc_g1.len());
+    pub fn vk_hash(&self) -> &Hash {
+        &self.vk_hash
+    }
+
+    pub fn verify_proof(&self, proof: &Proof) -> Result<bool, HVMError> {
+        if proof.version != PROOF_FORMAT_VERSION {
+            return Err(HVMError::Verifier(format!("Unsupported proof format version: {}", proof.version)));
+        }
+        if proof.system != ProofSystem::Groth16Bn254 {
+            return Err(HVMError::Verifier(format!("Unsupported proof system: {:?}", proof.system)));
+        }
+        if proof.vk_hash != self.vk_hash {
+            return Err(HVMError::Verifier("Proof was produced for a different verifying key".to_string()));
+        }
+
+        let public_inputs: Vec<Fr> = proof.field_inputs();
 
         if (public_inputs.len() + 1) != self.verifying_key.vk.gamma_abc_g1.len() {
             return Err(HVMError::Verifier("Malformed verifying key".to_string()));
         }
 
-        println!("Verifying proof: {:?}", proof);
         let groth16_proof = ark_groth16::Proof::<Bn254>::deserialize_uncompressed(&proof.data[..])
             .map_err(|e| HVMError::Verifier(format!("Failed to deserialize proof: {}", e)))?;
-        
-        let pvk = &self.verifying_key;
-        let mut g_ic = pvk.vk.gamma_abc_g1[0].into_group();
-        for (i, b) in public_inputs.iter().zip(pvk.vk.gamma_abc_g1.iter().skip(1)) {
-            g_ic.add_assign(&b.mul_bigint(i.into_bigint()));
-        }
-        
-        let result = Groth16::<Bn254>::verify_with_processed_vk(pvk, &public_inputs, &groth16_proof)
-            .map_err(|e| HVMError::Verifier(format!("Proof verification failed: {}", e)));
-        println!("Verification result: {:?}", result);
 
-        result.map_err(|e| HVMError::Verifier(format!("Proof verification failed: {}", e)))
+        Groth16::<Bn254>::verify_with_processed_vk(&self.verifying_key, &public_inputs, &groth16_proof)
+            .map_err(|e| HVMError::Verifier(format!("Proof verification failed: {}", e)))
     }
 }
 
-pub fn create_zk_verifier(verifying_key: VerifyingKey<Bn254>) -> ZKVerifier {
+pub fn create_zk_verifier(verifying_key: VerifyingKey<Bn254>) -> Result<ZKVerifier, HVMError> {
     ZKVerifier::new(verifying_key)
 }
\ No newline at end of file
@@ -0,0 +1,42 @@
+use super::merkle::{self, Hash, EMPTY_LEAF, TREE_DEPTH};
+use super::Account;
+use serde::{Serialize, Deserialize};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AccountProof {
+    pub address: String,
+    pub account: Option<Account>,
+    #[serde(with = "hex::serde")]
+    pub root: Hash,
+    #[serde(with = "super::encoding::hex_list")]
+    pub siblings: Vec<Hash>,
+}
+
+impl AccountProof {
+    pub fn is_inclusion(&self) -> bool {
+        self.account.is_some()
+    }
+
+    pub fn balance(&self) -> u128 {
+        self.account.as_ref().map_or(0, |account| account.balance)
+    }
+
+    pub fn nonce(&self) -> u64 {
+        self.account.as_ref().map_or(0, |account| account.nonce)
+    }
+}
+
+pub fn verify_account_proof(proof: &AccountProof, root: &Hash) -> bool {
+    if proof.siblings.len() != TREE_DEPTH || proof.root != *root {
+        return false;
+    }
+
+    let key = merkle::hash_key(&proof.address);
+    let leaf = match &proof.account {
+        Some(account) if account.is_empty() => return false,
+        Some(account) => account.leaf_hash(&key),
+        None => EMPTY_LEAF,
+    };
+
+    merkle::compute_root(&key, leaf, &proof.siblings) == *root
+}
\ No newline at end of file
@@ -0,0 +1,336 @@
+use super::merkle::{self, Hash, TREE_DEPTH};
+use super::poseidon;
+use super::{Account, StateTransition};
+use crate::error::HVMError;
+use ark_bn254::Fr;
+use ark_crypto_primitives::crh::CRHSchemeGadget;
+use ark_crypto_primitives::crh::poseidon::constraints::{CRHGadget, CRHParametersVar};
+use ark_r1cs_std::prelude::*;
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystem, ConstraintSystemRef, SynthesisError, SynthesisMode};
+use serde::{Serialize, Deserialize};
+use std::sync::OnceLock;
+
+const BALANCE_BITS: usize = 128;
+const KEY_LIMB_BITS: usize = 128;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AccountUpdate {
+    pub address: String,
+    pub key: Hash,
+    pub old_account: Account,
+    pub new_account: Account,
+    pub siblings: Vec<Hash>,
+}
+
+impl AccountUpdate {
+    pub fn blank() -> Self {
+        Self {
+            address: String::new(),
+            key: [0u8; 32],
+            old_account: Account::default(),
+            new_account: Account::default(),
+            siblings: vec![[0u8; 32]; TREE_DEPTH],
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Transfer {
+    pub amount: u128,
+    pub fee: u128,
+    pub nonce: u64,
+    pub fee_credit: bool,
+    pub debit: Option<AccountUpdate>,
+    pub credit: Option<AccountUpdate>,
+}
+
+impl Transfer {
+    pub fn deposit(amount: u128, credit: AccountUpdate) -> Self {
+        Self { amount, fee: 0, nonce: 0, fee_credit: false, debit: None, credit: Some(credit) }
+    }
+
+    pub fn debit(amount: u128, fee: u128, nonce: u64, debit: AccountUpdate) -> Self {
+        Self { amount, fee, nonce, fee_credit: false, debit: Some(debit), credit: None }
+    }
+
+    pub fn payment(amount: u128, fee: u128, nonce: u64, debit: AccountUpdate, credit: AccountUpdate) -> Self {
+        Self { amount, fee, nonce, fee_credit: false, debit: Some(debit), credit: Some(credit) }
+    }
+
+    pub fn fees(amount: u128, credit: AccountUpdate) -> Self {
+        Self { amount, fee: 0, nonce: 0, fee_credit: true, debit: None, credit: Some(credit) }
+    }
+
+    pub fn tag(&self) -> u64 {
+        self.debit.is_some() as u64 | (self.credit.is_some() as u64) << 1 | (self.fee_credit as u64) << 2
+    }
+
+    fn record(&self) -> [Fr; 8] {
+        let (debit_high, debit_low) = poseidon::key_limbs(&self.debit.as_ref().map_or([0u8; 32], |update| update.key));
+        let (credit_high, credit_low) = poseidon::key_limbs(&self.credit.as_ref().map_or([0u8; 32], |update| update.key));
+        [
+            Fr::from(self.tag()),
+            debit_high,
+            debit_low,
+            credit_high,
+            credit_low,
+            Fr::from(self.amount),
+            Fr::from(self.fee),
+            Fr::from(self.nonce),
+        ]
+    }
+}
+
+pub fn capacity_for(max_transactions: usize) -> usize {
+    max_transactions + 1
+}
+
+pub fn constraints_per_transfer() -> u64 {
+    static PER_TRANSFER: OnceLock<u64> = OnceLock::new();
+    *PER_TRANSFER.get_or_init(|| count_constraints(1) - count_constraints(0))
+}
+
+pub fn constraints_for(transfers: usize) -> u64 {
+    transfers as u64 * constraints_per_transfer()
+}
+
+fn count_constraints(capacity: usize) -> u64 {
+    let cs = ConstraintSystem::<Fr>::new_ref();
+    cs.set_mode(SynthesisMode::Setup);
+    StateTransitionCircuit::blank(capacity)
+        .generate_constraints(cs.clone())
+        .expect("Blank circuit synthesis is infallible");
+    cs.num_constraints() as u64
+}
+
+pub fn batch_commitment(batch_digest: &Hash, withdrawals_root: &Hash, transfers: &[Transfer]) -> Fr {
+    let seed = poseidon::hash(&[poseidon::to_field(batch_digest), poseidon::to_field(withdrawals_root)]);
+    transfers.iter().fold(seed, |commitment, transfer| {
+        let mut inputs = vec![commitment];
+        inputs.extend(transfer.record());
+        poseidon::hash(&inputs)
+    })
+}
+
+pub fn public_inputs(transition: &StateTransition, batch_digest: &Hash) -> Vec<Fr> {
+    vec![
+        poseidon::to_field(&transition.pre_root),
+        poseidon::to_field(&transition.post_state.root()),
+        poseidon::to_field(&transition.withdrawal_root()),
+        poseidon::to_field(batch_digest),
+        batch_commitment(batch_digest, &transition.withdrawal_root(), &transition.transfers),
+    ]
+}
+
+#[derive(Clone, Debug)]
+pub struct StateTransitionCircuit {
+    pub capacity: usize,
+    pub old_root: Hash,
+    pub new_root: Hash,
+    pub withdrawals_root: Hash,
+    pub batch_digest: Hash,
+    pub transfers: Vec<Transfer>,
+}
+
+impl StateTransitionCircuit {
+    pub fn new(capacity: usize, transition: &StateTransition, batch_digest: Hash) -> Result<Self, HVMError> {
+        if transition.transfers.len() > capacity {
+            return Err(HVMError::Prover(format!(
+                "Batch requires {} transfers but circuit capacity is {}",
+                transition.transfers.len(),
+                capacity
+            )));
+        }
+        Ok(Self {
+            capacity,
+            old_root: transition.pre_root,
+            new_root: transition.post_state.root(),
+            withdrawals_root: transition.withdrawal_root(),
+            batch_digest,
+            transfers: transition.transfers.clone(),
+        })
+    }
+
+    pub fn blank(capacity: usize) -> Self {
+        Self {
+            capacity,
+            old_root: merkle::default_hashes()[0],
+            new_root: merkle::default_hashes()[0],
+            withdrawals_root: [0u8; 32],
+            batch_digest: [0u8; 32],
+            transfers: Vec::new(),
+        }
+    }
+
+    pub fn public_inputs(&self) -> Vec<Fr> {
+        vec![
+            poseidon::to_field(&self.old_root),
+            poseidon::to_field(&self.new_root),
+            poseidon::to_field(&self.withdrawals_root),
+            poseidon::to_field(&self.batch_digest),
+            batch_commitment(&self.batch_digest, &self.withdrawals_root, &self.transfers),
+        ]
+    }
+}
+
+impl ConstraintSynthesizer<Fr> for StateTransitionCircuit {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> Result<(), SynthesisError> {
+        let public_inputs = self.public_inputs();
+        let params = CRHParametersVar { parameters: poseidon::config().clone() };
+
+        let old_root = FpVar::new_input(cs.clone(), || Ok(public_inputs[0]))?;
+        let new_root = FpVar::new_input(cs.clone(), || Ok(public_inputs[1]))?;
+        let withdrawals_root = FpVar::new_input(cs.clone(), || Ok(public_inputs[2]))?;
+        let batch_digest = FpVar::new_input(cs.clone(), || Ok(public_inputs[3]))?;
+        let commitment = FpVar::new_input(cs.clone(), || Ok(public_inputs[4]))?;
+
+        let mut current_root = old_root;
+        let mut current_commitment = CRHGadget::<Fr>::evaluate(&params, &[batch_digest, withdrawals_root])?;
+        let mut debited_fees = FpVar::zero();
+
+        for slot in 0..self.capacity {
+            let transfer = self.transfers.get(slot);
+            let has_debit = Boolean::new_witness(cs.clone(), || Ok(transfer.is_some_and(|transfer| transfer.debit.is_some())))?;
+            let has_credit = Boolean::new_witness(cs.clone(), || Ok(transfer.is_some_and(|transfer| transfer.credit.is_some())))?;
+            let fee_credit = Boolean::new_witness(cs.clone(), || Ok(transfer.is_some_and(|transfer| transfer.fee_credit)))?;
+            let amount = FpVar::new_witness(cs.clone(), || Ok(Fr::from(transfer.map_or(0, |transfer| transfer.amount))))?;
+            let fee = FpVar::new_witness(cs.clone(), || Ok(Fr::from(transfer.map_or(0, |transfer| transfer.fee))))?;
+            let nonce = FpVar::new_witness(cs.clone(), || Ok(Fr::from(transfer.map_or(0, |transfer| transfer.nonce))))?;
+            enforce_balance_range(&amount)?;
+            enforce_balance_range(&fee)?;
+
+            let debit = UpdateVar::new(cs.clone(), transfer.and_then(|transfer| transfer.debit.as_ref()))?;
+            debit.old_nonce.conditional_enforce_equal(&nonce, &has_debit)?;
+            let debited = &debit.old_balance - &amount - &fee;
+            let bumped = &debit.old_nonce + FpVar::one();
+            current_root = debit.apply(&params, &current_root, &has_debit, &debited, &bumped)?;
+            debited_fees = has_debit.select(&(&debited_fees + &fee), &debited_fees)?;
+
+            let credit = UpdateVar::new(cs.clone(), transfer.and_then(|transfer| transfer.credit.as_ref()))?;
+            let credited = &credit.old_balance + &amount;
+            current_root = credit.apply(&params, &current_root, &has_credit, &credited, &credit.old_nonce)?;
+            amount.conditional_enforce_equal(&debited_fees, &fee_credit)?;
+
+            let tag = FpVar::from(has_debit.clone())
+                + FpVar::from(has_credit.clone()) * Fr::from(2u64)
+                + FpVar::from(fee_credit) * Fr::from(4u64);
+            let next_commitment = CRHGadget::<Fr>::evaluate(&params, &[
+                current_commitment.clone(),
+                tag,
+                debit.key_high,
+                debit.key_low,
+                credit.key_high,
+                credit.key_low,
+                amount,
+                fee,
+                nonce,
+            ])?;
+            let active = has_debit.or(&has_credit)?;
+            current_commitment = active.select(&next_commitment, &current_commitment)?;
+        }
+
+        current_root.enforce_equal(&new_root)?;
+        current_commitment.enforce_equal(&commitment)?;
+
+        Ok(())
+    }
+}
+
+struct UpdateVar {
+    key_bits: Vec<Boolean<Fr>>,
+    key_high: FpVar<Fr>,
+    key_low: FpVar<Fr>,
+    siblings: Vec<FpVar<Fr>>,
+    old_balance: FpVar<Fr>,
+    old_nonce: FpVar<Fr>,
+    storage: FpVar<Fr>,
+}
+
+impl UpdateVar {
+    fn new(cs: ConstraintSystemRef<Fr>, update: Option<&AccountUpdate>) -> Result<Self, SynthesisError> {
+        let update = update.cloned().unwrap_or_else(AccountUpdate::blank);
+        let key_bits = (0..KEY_LIMB_BITS)
+            .map(|index| Boolean::new_witness(cs.clone(), || Ok(merkle::bit(&update.key, index))))
+            .collect::<Result<Vec<_>, _>>()?;
+        let key_high = Boolean::le_bits_to_fp_var(&key_bits.iter().rev().cloned().collect::<Vec<_>>())?;
+        let key_low = FpVar::new_witness(cs.clone(), || Ok(poseidon::key_limbs(&update.key).1))?;
+        let siblings = update.siblings
+            .iter()
+            .map(|sibling| FpVar::new_witness(cs.clone(), || Ok(poseidon::to_field(sibling))))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self {
+            key_bits,
+            key_high,
+            key_low,
+            siblings,
+            old_balance: FpVar::new_witness(cs.clone(), || Ok(Fr::from(update.old_account.balance)))?,
+            old_nonce: FpVar::new_witness(cs.clone(), || Ok(Fr::from(update.old_account.nonce)))?,
+            storage: FpVar::new_witness(cs, || Ok(poseidon::to_field(&update.old_account.storage_root)))?,
+        })
+    }
+
+    fn apply(
+        &self,
+        params: &CRHParametersVar<Fr>,
+        current_root: &FpVar<Fr>,
+        active: &Boolean<Fr>,
+        new_balance: &FpVar<Fr>,
+        new_nonce: &FpVar<Fr>,
+    ) -> Result<FpVar<Fr>, SynthesisError> {
+        enforce_balance_range(&active.select(new_balance, &FpVar::zero())?)?;
+        let old_leaf = leaf_var(params, &self.key_high, &self.key_low, &self.old_balance, &self.old_nonce, &self.storage)?;
+        let new_leaf = leaf_var(params, &self.key_high, &self.key_low, new_balance, new_nonce, &self.storage)?;
+        let computed_old_root = root_var(params, old_leaf, &self.key_bits[..TREE_DEPTH], &self.siblings)?;
+        let computed_new_root = root_var(params, new_leaf, &self.key_bits[..TREE_DEPTH], &self.siblings)?;
+        computed_old_root.conditional_enforce_equal(current_root, active)?;
+        active.select(&computed_new_root, current_root)
+    }
+}
+
+fn enforce_balance_range(value: &FpVar<Fr>) -> Result<(), SynthesisError> {
+    for bit in value.to_bits_le()?.iter().skip(BALANCE_BITS) {
+        bit.enforce_equal(&Boolean::FALSE)?;
+    }
+    Ok(())
+}
+
+fn leaf_var(
+    params: &CRHParametersVar<Fr>,
+    key_high: &FpVar<Fr>,
+    key_low: &FpVar<Fr>,
+    balance: &FpVar<Fr>,
+    nonce: &FpVar<Fr>,
+    storage: &FpVar<Fr>,
+) -> Result<FpVar<Fr>, SynthesisError> {
+    let zero = FpVar::zero();
+    let is_empty = Boolean::kary_and(&[
+        balance.is_eq(&zero)?,
+        nonce.is_eq(&zero)?,
+        storage.is_eq(&zero)?,
+    ])?;
+    let hash = CRHGadget::<Fr>::evaluate(params, &[
+        key_high.clone(),
+        key_low.clone(),
+        balance.clone(),
+        nonce.clone(),
+        storage.clone(),
+    ])?;
+    is_empty.select(&zero, &hash)
+}
+
+fn root_var(
+    params: &CRHParametersVar<Fr>,
+    leaf: FpVar<Fr>,
+    key_bits: &[Boolean<Fr>],
+    siblings: &[FpVar<Fr>],
+) -> Result<FpVar<Fr>, SynthesisError> {
+    let mut current = leaf;
+    for depth in (0..TREE_DEPTH).rev() {
+        let sibling = &siblings[TREE_DEPTH - 1 - depth];
+        let left = key_bits[depth].select(sibling, &current)?;
+        let right = key_bits[depth].select(&current, sibling)?;
+        current = CRHGadget::<Fr>::evaluate(params, &[left, right])?;
+    }
+    Ok(current)
+}
\ No newline at end of file
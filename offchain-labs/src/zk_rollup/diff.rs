@@ -0,0 +1,99 @@
+use super::encoding::ByteReader;
+use super::merkle::{self, Hash};
+use super::{Account, State, TouchedAccounts};
+use crate::error::HVMError;
+use serde::{Serialize, Deserialize};
+
+pub const DIFF_FORMAT_VERSION: u8 = 2;
+const DIFF_MAGIC: &[u8; 4] = b"HVMD";
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StateDiff {
+    #[serde(with = "hex::serde")]
+    pub batch_id: Hash,
+    #[serde(with = "hex::serde")]
+    pub pre_root: Hash,
+    #[serde(with = "hex::serde")]
+    pub post_root: Hash,
+    pub accounts: Vec<(String, Account)>,
+}
+
+impl StateDiff {
+    pub fn new(batch_id: Hash, pre_root: Hash, post_root: Hash, touched: &TouchedAccounts) -> Self {
+        Self { batch_id, pre_root, post_root, accounts: touched.changes().collect() }
+    }
+
+    pub fn apply(&self, state: &mut State) -> Result<(), HVMError> {
+        if state.root() != self.pre_root {
+            return Err(HVMError::ZKRollup(format!("State diff for batch {} does not extend the current root", merkle::to_hex(&self.batch_id))));
+        }
+        let mut next = state.clone();
+        if next.update_accounts(self.accounts.iter().cloned()) != self.post_root {
+            return Err(HVMError::ZKRollup(format!("State diff for batch {} does not reach its post root", merkle::to_hex(&self.batch_id))));
+        }
+        *state = next;
+        Ok(())
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(DIFF_MAGIC);
+        bytes.push(DIFF_FORMAT_VERSION);
+        bytes.extend_from_slice(&self.batch_id);
+        bytes.extend_from_slice(&self.pre_root);
+        bytes.extend_from_slice(&self.post_root);
+        bytes.extend_from_slice(&(self.accounts.len() as u32).to_le_bytes());
+        for (address, account) in &self.accounts {
+            bytes.extend_from_slice(&(address.len() as u32).to_le_bytes());
+            bytes.extend_from_slice(address.as_bytes());
+            bytes.extend_from_slice(&account.balance.to_le_bytes());
+            bytes.extend_from_slice(&account.nonce.to_le_bytes());
+            bytes.extend_from_slice(&account.storage_root);
+        }
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, HVMError> {
+        let mut reader = ByteReader::new(bytes);
+        if reader.take(4)? != DIFF_MAGIC {
+            return Err(HVMError::ZKRollup("Invalid state diff magic".to_string()));
+        }
+        let version = reader.take(1)?[0];
+        if version != DIFF_FORMAT_VERSION {
+            return Err(HVMError::ZKRollup(format!("Unsupported state diff version: {}", version)));
+        }
+        let batch_id = reader.take_hash()?;
+        let pre_root = reader.take_hash()?;
+        let post_root = reader.take_hash()?;
+        let count = reader.take_u32()? as usize;
+
+        let mut accounts = Vec::new();
+        for _ in 0..count {
+            let address_len = reader.take_u32()? as usize;
+            let address = String::from_utf8(reader.take(address_len)?.to_vec())
+                .map_err(|e| HVMError::ZKRollup(format!("Invalid address in state diff: {}", e)))?;
+            let account = Account {
+                balance: reader.take_u128()?,
+                nonce: reader.take_u64()?,
+                storage_root: reader.take_hash()?,
+            };
+            accounts.push((address, account));
+        }
+        if !reader.is_empty() {
+            return Err(HVMError::ZKRollup("Trailing bytes after state diff".to_string()));
+        }
+
+        Ok(Self { batch_id, pre_root, post_root, accounts })
+    }
+}
+
+pub fn rebuild_state<'a, I>(genesis: &State, diffs: I) -> Result<State, HVMError>
+where
+    I: IntoIterator<Item = &'a StateDiff>,
+{
+    let mut state = genesis.clone();
+    for diff in diffs {
+        diff.apply(&mut state)?;
+    }
+    Ok(state)
+}
\ No newline at end of file
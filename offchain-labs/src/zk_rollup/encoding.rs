@@ -0,0 +1,156 @@
+use super::merkle::Hash;
+use crate::error::HVMError;
+
+pub(crate) struct ByteReader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, offset: 0 }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.offset == self.bytes.len()
+    }
+
+    pub fn take(&mut self, len: usize) -> Result<&'a [u8], HVMError> {
+        let end = self.offset.checked_add(len)
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or_else(|| HVMError::ZKRollup("Truncated encoding".to_string()))?;
+        let slice = &self.bytes[self.offset..end];
+        self.offset = end;
+        Ok(slice)
+    }
+
+    pub fn take_u32(&mut self) -> Result<u32, HVMError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    pub fn take_u64(&mut self) -> Result<u64, HVMError> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    pub fn take_u128(&mut self) -> Result<u128, HVMError> {
+        Ok(u128::from_le_bytes(self.take(16)?.try_into().unwrap()))
+    }
+
+    pub fn take_hash(&mut self) -> Result<Hash, HVMError> {
+        Ok(self.take(32)?.try_into().unwrap())
+    }
+
+    pub fn take_compact(&mut self) -> Result<u64, HVMError> {
+        let prefix = self.take(1)?[0];
+        let (value, min) = match prefix & 0b11 {
+            0 => return Ok(u64::from(prefix >> 2)),
+            1 => (u64::from(u16::from_le_bytes([prefix, self.take(1)?[0]]) >> 2), 1 << 6),
+            2 => {
+                let mut bytes = [prefix, 0, 0, 0];
+                bytes[1..].copy_from_slice(self.take(3)?);
+                (u64::from(u32::from_le_bytes(bytes) >> 2), 1 << 14)
+            }
+            _ => {
+                let len = usize::from(prefix >> 2) + 4;
+                if len > 8 {
+                    return Err(HVMError::ZKRollup("Compact integer exceeds 64 bits".to_string()));
+                }
+                let mut bytes = [0u8; 8];
+                bytes[..len].copy_from_slice(self.take(len)?);
+                (u64::from_le_bytes(bytes), 1 << 30)
+            }
+        };
+        if value < min || (prefix & 0b11 == 3 && value >> (8 * (usize::from(prefix >> 2) + 3)) == 0) {
+            return Err(HVMError::ZKRollup("Non-canonical compact integer".to_string()));
+        }
+        Ok(value)
+    }
+
+    pub fn take_bytes(&mut self) -> Result<&'a [u8], HVMError> {
+        let len = usize::try_from(self.take_compact()?)
+            .map_err(|_| HVMError::ZKRollup("Length prefix overflows".to_string()))?;
+        self.take(len)
+    }
+
+    pub fn take_string(&mut self) -> Result<String, HVMError> {
+        String::from_utf8(self.take_bytes()?.to_vec())
+            .map_err(|e| HVMError::ZKRollup(format!("Invalid UTF-8 string: {}", e)))
+    }
+}
+
+pub(crate) fn put_compact(out: &mut Vec<u8>, value: u64) {
+    match value {
+        0..=0x3f => out.push((value as u8) << 2),
+        0x40..=0x3fff => out.extend_from_slice(&(((value as u16) << 2) | 0b01).to_le_bytes()),
+        0x4000..=0x3fff_ffff => out.extend_from_slice(&(((value as u32) << 2) | 0b10).to_le_bytes()),
+        _ => {
+            let len = 8 - value.leading_zeros() as usize / 8;
+            out.push((((len - 4) as u8) << 2) | 0b11);
+            out.extend_from_slice(&value.to_le_bytes()[..len]);
+        }
+    }
+}
+
+pub(crate) fn put_bytes(out: &mut Vec<u8>, bytes: &[u8]) {
+    put_compact(out, bytes.len() as u64);
+    out.extend_from_slice(bytes);
+}
+
+pub(crate) mod hex_option {
+    use crate::zk_rollup::merkle::Hash;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S>(value: &Option<Hash>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match value {
+            Some(hash) => serializer.serialize_some(&hex::encode(hash)),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<Hash>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Option::<String>::deserialize(deserializer)?
+            .map(|value| {
+                let mut hash = [0u8; 32];
+                hex::decode_to_slice(value, &mut hash).map_err(serde::de::Error::custom)?;
+                Ok(hash)
+            })
+            .transpose()
+    }
+}
+
+pub(crate) mod hex_list {
+    use crate::zk_rollup::merkle::Hash;
+    use serde::{Deserialize, Deserializer, Serializer};
+    use serde::ser::SerializeSeq;
+
+    pub fn serialize<S>(values: &[Hash], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut seq = serializer.serialize_seq(Some(values.len()))?;
+        for value in values {
+            seq.serialize_element(&hex::encode(value))?;
+        }
+        seq.end()
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Vec<Hash>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Vec::<String>::deserialize(deserializer)?
+            .iter()
+            .map(|value| {
+                let mut hash = [0u8; 32];
+                hex::decode_to_slice(value, &mut hash).map_err(serde::de::Error::custom)?;
+                Ok(hash)
+            })
+            .collect()
+    }
+}
\ No newline at end of file
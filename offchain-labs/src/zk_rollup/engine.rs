@@ -0,0 +1,93 @@
+use super::circuit::{self, StateTransitionCircuit};
+use super::{merkle, transition, Proof, State, TransactionOutcome, ZKRollup};
+use crate::bend::BendProgram;
+use crate::config::Config;
+use crate::error::HVMError;
+use crate::prover::{ResourceUsage, ZKProver};
+use crate::sequencer::Batch;
+use crate::verifier::ZKVerifier;
+use ark_bn254::Bn254;
+use ark_groth16::{Groth16, ProvingKey, VerifyingKey};
+use ark_snark::SNARK;
+
+pub struct RollupEngine {
+    prover: ZKProver,
+    verifier: ZKVerifier,
+}
+
+impl RollupEngine {
+    pub fn new(prover: ZKProver, verifier: ZKVerifier) -> Self {
+        Self { prover, verifier }
+    }
+
+    pub fn setup(config: &Config) -> Result<Self, HVMError> {
+        let capacity = circuit::capacity_for(config.prover_config.max_batch_size);
+        let (pk, vk) = Self::generate_zk_keys(capacity)?;
+        Ok(Self::new(ZKProver::new(pk, capacity)?, ZKVerifier::new(vk)?))
+    }
+
+    pub fn prover(&self) -> &ZKProver {
+        &self.prover
+    }
+
+    pub fn verifier(&self) -> &ZKVerifier {
+        &self.verifier
+    }
+
+    fn generate_zk_keys(capacity: usize) -> Result<(ProvingKey<Bn254>, VerifyingKey<Bn254>), HVMError> {
+        let circuit = StateTransitionCircuit::blank(capacity);
+        let mut rng = ark_std::rand::thread_rng();
+        Groth16::<Bn254>::circuit_specific_setup(circuit, &mut rng)
+            .map_err(|e| HVMError::Setup(format!("Failed to generate ZK-SNARK keys: {}", e)))
+    }
+}
+
+impl ZKRollup for RollupEngine {
+    fn generate_proof(&self, state: &State, batch: &Batch) -> Result<Proof, HVMError> {
+        self.prover.generate_proof(state, batch)
+    }
+
+    fn verify_proof(&self, proof: &Proof) -> Result<bool, HVMError> {
+        self.verifier.verify_proof(proof)
+    }
+
+    fn apply_proof(&self, state: &mut State, proof: &Proof, batch: &Batch) -> Result<Vec<TransactionOutcome>, HVMError> {
+        if proof.batch_id != batch.batch_id() {
+            return Err(HVMError::ZKRollup(format!(
+                "Proof is for batch {} but batch {} was supplied",
+                merkle::to_hex(&proof.batch_id),
+                merkle::to_hex(&batch.batch_id())
+            )));
+        }
+        if state.root() != batch.header().pre_state_root {
+            return Err(HVMError::ZKRollup(format!("Batch {} does not extend the current state root", merkle::to_hex(&batch.batch_id()))));
+        }
+        let transition = transition::apply_batch(state, batch, self.prover.programs())?;
+        if transition.post_state.root() != batch.header().post_state_root {
+            return Err(HVMError::ZKRollup(format!("Batch {} does not reach its declared post state root", merkle::to_hex(&batch.batch_id()))));
+        }
+        if transition.withdrawal_root() != batch.header().withdrawals_root {
+            return Err(HVMError::ZKRollup(format!("Batch {} does not commit to its withdrawals", merkle::to_hex(&batch.batch_id()))));
+        }
+        if transition.rejected_deposits.iter().map(|(id, _)| *id).ne(batch.rejected_deposits().iter().copied()) {
+            return Err(HVMError::ZKRollup(format!("Batch {} does not record its rejected deposits", merkle::to_hex(&batch.batch_id()))));
+        }
+        if proof.field_inputs() != circuit::public_inputs(&transition, &batch.batch_id()) {
+            return Err(HVMError::ZKRollup("Proof public inputs do not match the batch transition".to_string()));
+        }
+        *state = transition.post_state;
+        Ok(transition.outcomes)
+    }
+
+    fn register_program(&mut self, program: BendProgram) {
+        self.prover.add_program(program);
+    }
+
+    fn unregister_program(&mut self, program_id: &str) {
+        self.prover.remove_program(program_id);
+    }
+
+    fn estimate_resource_usage(&self, program: &BendProgram) -> Result<ResourceUsage, HVMError> {
+        self.prover.estimate_resource_usage(program)
+    }
+}
\ No newline at end of file
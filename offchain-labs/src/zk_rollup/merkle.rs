@@ -0,0 +1,190 @@
+use super::poseidon;
+use sha2::{Sha256, Digest};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::OnceLock;
+
+pub type Hash = [u8; 32];
+
+pub const TREE_DEPTH: usize = 64;
+pub const EMPTY_LEAF: Hash = [0u8; 32];
+
+#[derive(Clone, Default, PartialEq)]
+pub struct SparseMerkleTree {
+    nodes: HashMap<(usize, Hash), Hash>,
+}
+
+impl SparseMerkleTree {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn root(&self) -> Hash {
+        self.node(0, &[0u8; 32])
+    }
+
+    pub fn get_leaf(&self, key: &Hash) -> Hash {
+        self.node(TREE_DEPTH, &prefix(key, TREE_DEPTH))
+    }
+
+    pub fn update(&mut self, key: &Hash, leaf: Hash) -> Hash {
+        let mut current = leaf;
+        self.set_node(TREE_DEPTH, prefix(key, TREE_DEPTH), current);
+
+        for depth in (0..TREE_DEPTH).rev() {
+            let sibling = self.node(depth + 1, &sibling_prefix(key, depth + 1));
+            current = if bit(key, depth) {
+                hash_node(&sibling, &current)
+            } else {
+                hash_node(&current, &sibling)
+            };
+            self.set_node(depth, prefix(key, depth), current);
+        }
+
+        current
+    }
+
+    pub fn siblings(&self, key: &Hash) -> Vec<Hash> {
+        (1..=TREE_DEPTH)
+            .rev()
+            .map(|depth| self.node(depth, &sibling_prefix(key, depth)))
+            .collect()
+    }
+
+    fn node(&self, depth: usize, prefix: &Hash) -> Hash {
+        self.nodes
+            .get(&(depth, *prefix))
+            .copied()
+            .unwrap_or_else(|| default_hashes()[depth])
+    }
+
+    fn set_node(&mut self, depth: usize, prefix: Hash, hash: Hash) {
+        if hash == default_hashes()[depth] {
+            self.nodes.remove(&(depth, prefix));
+        } else {
+            self.nodes.insert((depth, prefix), hash);
+        }
+    }
+}
+
+impl fmt::Debug for SparseMerkleTree {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SparseMerkleTree")
+            .field("root", &to_hex(&self.root()))
+            .field("nodes", &self.nodes.len())
+            .finish()
+    }
+}
+
+pub fn hash_node(left: &Hash, right: &Hash) -> Hash {
+    poseidon::from_field(poseidon::hash(&[poseidon::to_field(left), poseidon::to_field(right)]))
+}
+
+pub fn compute_root(key: &Hash, leaf: Hash, siblings: &[Hash]) -> Hash {
+    siblings
+        .iter()
+        .enumerate()
+        .fold(leaf, |current, (index, sibling)| {
+            if bit(key, TREE_DEPTH - 1 - index) {
+                hash_node(sibling, &current)
+            } else {
+                hash_node(&current, sibling)
+            }
+        })
+}
+
+pub fn dense_root(leaves: &[Hash]) -> Hash {
+    let mut level = leaves.to_vec();
+    if level.is_empty() {
+        return EMPTY_LEAF;
+    }
+    while level.len() > 1 {
+        level = next_dense_level(&level);
+    }
+    level[0]
+}
+
+pub fn dense_path(leaves: &[Hash], index: usize) -> Vec<Hash> {
+    let mut path = Vec::new();
+    let mut level = leaves.to_vec();
+    let mut position = index;
+    while level.len() > 1 {
+        path.push(level.get(position ^ 1).copied().unwrap_or(EMPTY_LEAF));
+        level = next_dense_level(&level);
+        position /= 2;
+    }
+    path
+}
+
+pub fn verify_dense_path(leaf: &Hash, index: usize, path: &[Hash], root: &Hash) -> bool {
+    if path.len() < usize::BITS as usize && index >> path.len() != 0 {
+        return false;
+    }
+    let computed = path.iter().enumerate().fold(*leaf, |current, (level, sibling)| {
+        if (index >> level) & 1 == 1 {
+            hash_pair(sibling, &current)
+        } else {
+            hash_pair(&current, sibling)
+        }
+    });
+    computed == *root
+}
+
+pub fn hash_key(address: &str) -> Hash {
+    Sha256::digest(address.as_bytes()).into()
+}
+
+pub fn shares_leaf(left: &str, right: &str) -> bool {
+    left != right && prefix(&hash_key(left), TREE_DEPTH) == prefix(&hash_key(right), TREE_DEPTH)
+}
+
+pub fn default_hashes() -> &'static [Hash] {
+    static DEFAULTS: OnceLock<Vec<Hash>> = OnceLock::new();
+    DEFAULTS.get_or_init(|| {
+        let mut defaults = vec![EMPTY_LEAF; TREE_DEPTH + 1];
+        for depth in (0..TREE_DEPTH).rev() {
+            defaults[depth] = hash_node(&defaults[depth + 1], &defaults[depth + 1]);
+        }
+        defaults
+    })
+}
+
+pub fn bit(key: &Hash, index: usize) -> bool {
+    (key[index / 8] >> (7 - index % 8)) & 1 == 1
+}
+
+pub fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn hash_pair(left: &Hash, right: &Hash) -> Hash {
+    let mut hasher = Sha256::new();
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
+fn next_dense_level(level: &[Hash]) -> Vec<Hash> {
+    level
+        .chunks(2)
+        .map(|pair| hash_pair(&pair[0], pair.get(1).unwrap_or(&EMPTY_LEAF)))
+        .collect()
+}
+
+fn prefix(key: &Hash, depth: usize) -> Hash {
+    let mut masked = [0u8; 32];
+    let full_bytes = depth / 8;
+    masked[..full_bytes].copy_from_slice(&key[..full_bytes]);
+    let partial_bits = depth % 8;
+    if partial_bits > 0 {
+        masked[full_bytes] = key[full_bytes] & (0xffu8 << (8 - partial_bits));
+    }
+    masked
+}
+
+fn sibling_prefix(key: &Hash, depth: usize) -> Hash {
+    let mut sibling = prefix(key, depth);
+    let index = depth - 1;
+    sibling[index / 8] ^= 1 << (7 - index % 8);
+    sibling
+}
\ No newline at end of file
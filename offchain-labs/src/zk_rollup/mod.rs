@@ -1,13 +1,33 @@
-mod proof;
+mod account_proof;
+pub(crate) mod encoding;
+mod engine;
 mod state;
+pub mod circuit;
+pub mod diff;
+pub mod merkle;
+pub mod poseidon;
+pub mod proof;
+pub mod transition;
+pub mod withdrawal;
 
-pub use proof::Proof;
-pub use state::State;
+pub use account_proof::{verify_account_proof, AccountProof};
+pub use diff::StateDiff;
+pub use engine::RollupEngine;
+pub use proof::{Proof, ProofSystem};
+pub use state::{Account, State};
+pub use transition::{StateTransition, TouchedAccounts, TransactionOutcome};
+pub use withdrawal::{Withdrawal, WithdrawalClaim};
 
+use crate::bend::BendProgram;
 use crate::error::HVMError;
+use crate::prover::ResourceUsage;
+use crate::sequencer::Batch;
 
 pub trait ZKRollup {
-    fn generate_proof(&self, state: &State, transaction: &[u8]) -> Result<Proof, HVMError>;
+    fn generate_proof(&self, state: &State, batch: &Batch) -> Result<Proof, HVMError>;
     fn verify_proof(&self, proof: &Proof) -> Result<bool, HVMError>;
-    fn apply_proof(&self, state: &mut State, proof: &Proof) -> Result<(), HVMError>;
+    fn apply_proof(&self, state: &mut State, proof: &Proof, batch: &Batch) -> Result<Vec<TransactionOutcome>, HVMError>;
+    fn register_program(&mut self, program: BendProgram);
+    fn unregister_program(&mut self, program_id: &str);
+    fn estimate_resource_usage(&self, program: &BendProgram) -> Result<ResourceUsage, HVMError>;
 }
\ No newline at end of file
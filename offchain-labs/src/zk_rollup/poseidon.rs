@@ -0,0 +1,47 @@
+use super::merkle::Hash;
+use ark_bn254::Fr;
+use ark_crypto_primitives::crh::{CRHScheme, poseidon::CRH};
+use ark_crypto_primitives::sponge::poseidon::{find_poseidon_ark_and_mds, PoseidonConfig};
+use ark_ff::{BigInteger, PrimeField};
+use std::sync::OnceLock;
+
+const FULL_ROUNDS: usize = 8;
+const PARTIAL_ROUNDS: usize = 57;
+const ALPHA: u64 = 5;
+const RATE: usize = 2;
+
+pub fn config() -> &'static PoseidonConfig<Fr> {
+    static CONFIG: OnceLock<PoseidonConfig<Fr>> = OnceLock::new();
+    CONFIG.get_or_init(|| {
+        let (ark, mds) = find_poseidon_ark_and_mds::<Fr>(
+            Fr::MODULUS_BIT_SIZE as u64,
+            RATE,
+            FULL_ROUNDS as u64,
+            PARTIAL_ROUNDS as u64,
+            0,
+        );
+        PoseidonConfig::new(FULL_ROUNDS, PARTIAL_ROUNDS, ALPHA, mds, ark, RATE, 1)
+    })
+}
+
+pub fn hash(inputs: &[Fr]) -> Fr {
+    CRH::<Fr>::evaluate(config(), inputs).expect("Poseidon evaluation is infallible")
+}
+
+pub fn to_field(hash: &Hash) -> Fr {
+    Fr::from_le_bytes_mod_order(hash)
+}
+
+pub fn from_field(value: Fr) -> Hash {
+    let mut hash = [0u8; 32];
+    hash.copy_from_slice(&value.into_bigint().to_bytes_le());
+    hash
+}
+
+pub fn key_limbs(key: &Hash) -> (Fr, Fr) {
+    let mut high = [0u8; 16];
+    let mut low = [0u8; 16];
+    high.copy_from_slice(&key[..16]);
+    low.copy_from_slice(&key[16..]);
+    (Fr::from(u128::from_be_bytes(high)), Fr::from(u128::from_be_bytes(low)))
+}
\ No newline at end of file
@@ -1,16 +1,128 @@
+use super::merkle::Hash;
+use super::encoding::ByteReader;
+use super::poseidon;
+use crate::error::HVMError;
+use ark_bn254::{Bn254, Fr};
+use ark_groth16::VerifyingKey;
+use ark_serialize::CanonicalSerialize;
 use serde::{Serialize, Deserialize};
+use sha2::{Sha256, Digest};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+pub const PROOF_FORMAT_VERSION: u8 = 2;
+const PROOF_MAGIC: &[u8; 4] = b"HVMP";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProofSystem {
+    Groth16Bn254,
+}
+
+impl ProofSystem {
+    pub fn id(&self) -> u8 {
+        match self {
+            ProofSystem::Groth16Bn254 => 1,
+        }
+    }
+
+    pub fn from_id(id: u8) -> Result<Self, HVMError> {
+        match id {
+            1 => Ok(ProofSystem::Groth16Bn254),
+            _ => Err(HVMError::ZKRollup(format!("Unknown proof system id: {}", id))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Proof {
+    pub version: u8,
+    pub system: ProofSystem,
+    #[serde(with = "hex::serde")]
+    pub vk_hash: Hash,
+    #[serde(with = "super::encoding::hex_list")]
+    pub public_inputs: Vec<Hash>,
+    #[serde(with = "hex::serde")]
+    pub batch_id: Hash,
+    #[serde(with = "hex::serde")]
     pub data: Vec<u8>,
 }
 
 impl Proof {
-    pub fn new(data: Vec<u8>) -> Self {
-        Self { data }
+    pub fn new(system: ProofSystem, vk_hash: Hash, public_inputs: &[Fr], batch_id: Hash, data: Vec<u8>) -> Self {
+        Self {
+            version: PROOF_FORMAT_VERSION,
+            system,
+            vk_hash,
+            public_inputs: public_inputs.iter().map(|input| poseidon::from_field(*input)).collect(),
+            batch_id,
+            data,
+        }
     }
 
     pub fn data(&self) -> &[u8] {
         &self.data
     }
+
+    pub fn field_inputs(&self) -> Vec<Fr> {
+        self.public_inputs.iter().map(poseidon::to_field).collect()
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(78 + 32 * self.public_inputs.len() + self.data.len());
+        bytes.extend_from_slice(PROOF_MAGIC);
+        bytes.push(self.version);
+        bytes.push(self.system.id());
+        bytes.extend_from_slice(&self.vk_hash);
+        bytes.extend_from_slice(&self.batch_id);
+        bytes.extend_from_slice(&(self.public_inputs.len() as u32).to_le_bytes());
+        for input in &self.public_inputs {
+            bytes.extend_from_slice(input);
+        }
+        bytes.extend_from_slice(&(self.data.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&self.data);
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, HVMError> {
+        let mut reader = ByteReader::new(bytes);
+        if reader.take(4)? != PROOF_MAGIC {
+            return Err(HVMError::ZKRollup("Invalid proof magic".to_string()));
+        }
+        let version = reader.take(1)?[0];
+        if version != PROOF_FORMAT_VERSION {
+            return Err(HVMError::ZKRollup(format!("Unsupported proof format version: {}", version)));
+        }
+        let system = ProofSystem::from_id(reader.take(1)?[0])?;
+        let vk_hash = reader.take_hash()?;
+        let batch_id = reader.take_hash()?;
+        let input_count = reader.take_u32()? as usize;
+        let public_inputs = (0..input_count)
+            .map(|_| reader.take_hash())
+            .collect::<Result<Vec<_>, _>>()?;
+        let data_len = reader.take_u32()? as usize;
+        let data = reader.take(data_len)?.to_vec();
+        if !reader.is_empty() {
+            return Err(HVMError::ZKRollup("Trailing bytes after proof".to_string()));
+        }
+
+        Ok(Self { version, system, vk_hash, public_inputs, batch_id, data })
+    }
+
+    pub fn to_json(&self) -> Result<String, HVMError> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, HVMError> {
+        let proof: Self = serde_json::from_str(json)?;
+        if proof.version != PROOF_FORMAT_VERSION {
+            return Err(HVMError::ZKRollup(format!("Unsupported proof format version: {}", proof.version)));
+        }
+        Ok(proof)
+    }
+}
+
+pub fn verifying_key_hash(verifying_key: &VerifyingKey<Bn254>) -> Result<Hash, HVMError> {
+    let mut bytes = Vec::new();
+    verifying_key.serialize_compressed(&mut bytes)
+        .map_err(|e| HVMError::ZKRollup(format!("Failed to serialize verifying key: {}", e)))?;
+    Ok(Sha256::digest(&bytes).into())
 }
\ No newline at end of file
@@ -1,11 +1,52 @@
-use super::Proof;
-use crate::error::HVMError;
+use super::merkle::{self, Hash, SparseMerkleTree, EMPTY_LEAF};
+use super::poseidon;
+use super::AccountProof;
+use ark_bn254::Fr;
 use serde::{Serialize, Deserialize};
+use std::collections::BTreeMap;
+
+#[derive(Default, Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct Account {
+    pub balance: u128,
+    pub nonce: u64,
+    #[serde(with = "hex::serde")]
+    pub storage_root: Hash,
+}
+
+impl Account {
+    pub fn new(balance: u128, nonce: u64) -> Self {
+        Self { balance, nonce, storage_root: [0u8; 32] }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    pub fn leaf_hash(&self, key: &Hash) -> Hash {
+        if self.is_empty() {
+            return EMPTY_LEAF;
+        }
+        let (key_high, key_low) = poseidon::key_limbs(key);
+        poseidon::from_field(poseidon::hash(&[
+            key_high,
+            key_low,
+            Fr::from(self.balance),
+            Fr::from(self.nonce),
+            poseidon::to_field(&self.storage_root),
+        ]))
+    }
+}
 
 #[derive(Default, Clone, Serialize, Deserialize, Debug, PartialEq)]
+#[serde(from = "StateAccounts", into = "StateAccounts")]
 pub struct State {
-    pub balance: u64,
-    pub nonce: u64,
+    accounts: BTreeMap<String, Account>,
+    tree: SparseMerkleTree,
+}
+
+#[derive(Serialize, Deserialize)]
+struct StateAccounts {
+    accounts: BTreeMap<String, Account>,
 }
 
 impl State {
@@ -13,18 +54,89 @@ impl State {
         Self::default()
     }
 
-    pub fn apply_proof(&mut self, proof: &Proof) -> Result<(), HVMError> {
-        self.balance += proof.data.len() as u64;
-        self.nonce += 1;
-        println!("State updated: balance = {}, nonce = {}", self.balance, self.nonce);
-        Ok(())
+    pub fn root(&self) -> Hash {
+        self.tree.root()
+    }
+
+    pub fn get_account(&self, address: &str) -> Option<&Account> {
+        self.accounts.get(address)
+    }
+
+    pub fn set_account(&mut self, address: &str, account: Account) -> Hash {
+        let key = merkle::hash_key(address);
+        let leaf = account.leaf_hash(&key);
+        if account.is_empty() {
+            self.accounts.remove(address);
+        } else {
+            self.accounts.insert(address.to_string(), account);
+        }
+        self.tree.update(&key, leaf)
     }
 
-    pub fn balance(&self) -> u64 {
-        self.balance
+    pub fn collides(&self, address: &str) -> bool {
+        let key = merkle::hash_key(address);
+        let own_leaf = self.get_account(address).map_or(EMPTY_LEAF, |account| account.leaf_hash(&key));
+        self.tree.get_leaf(&key) != own_leaf
     }
 
-    pub fn nonce(&self) -> u64 {
-        self.nonce
+    pub fn siblings(&self, address: &str) -> Vec<Hash> {
+        self.tree.siblings(&merkle::hash_key(address))
+    }
+
+    pub fn prove_account(&self, address: &str) -> AccountProof {
+        AccountProof {
+            address: address.to_string(),
+            account: self.get_account(address).cloned(),
+            root: self.root(),
+            siblings: self.siblings(address),
+        }
+    }
+
+    pub fn update_accounts<I>(&mut self, updates: I) -> Hash
+    where
+        I: IntoIterator<Item = (String, Account)>,
+    {
+        for (address, account) in updates {
+            self.set_account(&address, account);
+        }
+        self.root()
+    }
+
+    pub fn diff(&self, target: &State) -> Vec<(String, Account)> {
+        let mut changes: Vec<(String, Account)> = target.accounts
+            .iter()
+            .filter(|&(address, account)| self.accounts.get(address) != Some(account))
+            .map(|(address, account)| (address.clone(), account.clone()))
+            .collect();
+        changes.extend(
+            self.accounts
+                .keys()
+                .filter(|address| !target.accounts.contains_key(*address))
+                .map(|address| (address.clone(), Account::default())),
+        );
+        changes.sort_by(|a, b| a.0.cmp(&b.0));
+        changes
+    }
+
+    pub fn accounts(&self) -> impl Iterator<Item = (&String, &Account)> {
+        self.accounts.iter()
+    }
+
+    pub fn account_count(&self) -> usize {
+        self.accounts.len()
+    }
+}
+
+impl From<StateAccounts> for State {
+    fn from(snapshot: StateAccounts) -> Self {
+        let mut state = State::new();
+        state.update_accounts(snapshot.accounts);
+        state
+    }
+}
+
+impl From<State> for StateAccounts {
+    fn from(state: State) -> Self {
+        Self { accounts: state.accounts }
     }
 }
\ No newline at end of file
@@ -0,0 +1,287 @@
+use super::circuit::{AccountUpdate, Transfer};
+use super::merkle::{self, Hash};
+use super::withdrawal::{self, Withdrawal};
+use super::{Account, State};
+use crate::bend::{self, BendProgram};
+use crate::error::HVMError;
+use crate::sequencer::{Batch, DepositId, Transaction, TransactionKind};
+use serde::{Serialize, Deserialize};
+use std::collections::{BTreeMap, HashMap};
+
+pub const BASE_GAS: u64 = 21_000;
+pub const BYTE_GAS: u64 = 16;
+pub const PROGRAM_CALL_GAS: u64 = 50_000;
+pub const PROGRAM_GAS_LIMIT: u64 = 10_000_000;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TransactionOutcome {
+    pub success: bool,
+    pub error: Option<String>,
+    pub output: Vec<u8>,
+    #[serde(default)]
+    pub gas_used: u64,
+    #[serde(default)]
+    pub logs: Vec<String>,
+}
+
+impl TransactionOutcome {
+    pub fn success(output: Vec<u8>, gas_used: u64, logs: Vec<String>) -> Self {
+        Self { success: true, error: None, output, gas_used, logs }
+    }
+
+    pub fn failure(reason: String, gas_used: u64) -> Self {
+        Self { success: false, error: Some(reason), output: Vec::new(), gas_used, logs: Vec::new() }
+    }
+}
+
+pub fn intrinsic_gas(tx: &Transaction) -> u64 {
+    match &tx.kind {
+        TransactionKind::Transfer(_) | TransactionKind::Withdraw(_) => BASE_GAS,
+        TransactionKind::ProgramCall { calldata, .. } => BASE_GAS + PROGRAM_CALL_GAS + BYTE_GAS * calldata.len() as u64,
+        TransactionKind::DeployProgram(program) => BASE_GAS + BYTE_GAS * program.bytecode.len() as u64,
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct StateTransition {
+    pub pre_root: Hash,
+    pub post_state: State,
+    pub outcomes: Vec<TransactionOutcome>,
+    pub transfers: Vec<Transfer>,
+    pub withdrawals: Vec<Withdrawal>,
+    pub rejected_deposits: Vec<(DepositId, String)>,
+}
+
+impl StateTransition {
+    pub fn withdrawal_root(&self) -> Hash {
+        withdrawal::withdrawal_root(&self.withdrawals)
+    }
+
+    pub fn touched_accounts(&self) -> TouchedAccounts {
+        let mut accounts: BTreeMap<String, (Account, Account)> = BTreeMap::new();
+        for update in self.transfers.iter().flat_map(|transfer| transfer.debit.iter().chain(&transfer.credit)) {
+            accounts
+                .entry(update.address.clone())
+                .or_insert_with(|| (update.old_account.clone(), update.new_account.clone()))
+                .1 = update.new_account.clone();
+        }
+        TouchedAccounts { accounts }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TouchedAccounts {
+    accounts: BTreeMap<String, (Account, Account)>,
+}
+
+impl TouchedAccounts {
+    pub fn len(&self) -> usize {
+        self.accounts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.accounts.is_empty()
+    }
+
+    pub fn before(&self) -> impl Iterator<Item = (String, Account)> + '_ {
+        self.accounts.iter().map(|(address, (before, _))| (address.clone(), before.clone()))
+    }
+
+    pub fn after(&self) -> impl Iterator<Item = (String, Account)> + '_ {
+        self.accounts.iter().map(|(address, (_, after))| (address.clone(), after.clone()))
+    }
+
+    pub fn changes(&self) -> impl Iterator<Item = (String, Account)> + '_ {
+        self.accounts
+            .iter()
+            .filter(|(_, (before, after))| before != after)
+            .map(|(address, (_, after))| (address.clone(), after.clone()))
+    }
+}
+
+pub fn apply_batch(
+    pre_state: &State,
+    batch: &Batch,
+    programs: &HashMap<String, BendProgram>,
+) -> Result<StateTransition, HVMError> {
+    let mut post_state = pre_state.clone();
+    let mut transfers = Vec::new();
+    let mut deployed = HashMap::new();
+    let mut rejected_deposits = Vec::new();
+    for deposit in batch.deposits() {
+        if collides(&post_state, &deposit.recipient, batch) {
+            rejected_deposits.push((deposit.id(), format!("Recipient {} collides with another account", deposit.recipient)));
+            continue;
+        }
+        let mut recipient = post_state.get_account(&deposit.recipient).cloned().unwrap_or_default();
+        let Some(balance) = recipient.balance.checked_add(deposit.amount) else {
+            rejected_deposits.push((deposit.id(), format!("Deposit overflows the balance of {}", deposit.recipient)));
+            continue;
+        };
+        recipient.balance = balance;
+        transfers.push(Transfer::deposit(deposit.amount, write_account(&mut post_state, &deposit.recipient, recipient)));
+    }
+    let mut fees: u128 = 0;
+    let outcomes: Vec<TransactionOutcome> = batch
+        .transactions()
+        .iter()
+        .map(|tx| match apply_transaction(&mut post_state, &mut transfers, tx, batch, programs, &mut deployed) {
+            Ok((output, logs)) => {
+                fees = fees.saturating_add(tx.effective_fee(batch.base_fee()).unwrap_or_default());
+                let gas_used = intrinsic_gas(tx) + BYTE_GAS * output.len() as u64;
+                TransactionOutcome::success(output, gas_used, logs)
+            }
+            Err(reason) => TransactionOutcome::failure(reason, intrinsic_gas(tx)),
+        })
+        .collect();
+    if fees > 0 {
+        credit_fees(&mut post_state, &mut transfers, batch.fee_recipient(), fees)?;
+    }
+
+    let withdrawals = withdrawal::collect_withdrawals(batch, &outcomes);
+
+    Ok(StateTransition { pre_root: pre_state.root(), post_state, outcomes, transfers, withdrawals, rejected_deposits })
+}
+
+pub fn replay_batches<'a, I>(
+    genesis: &State,
+    batches: I,
+    programs: &HashMap<String, BendProgram>,
+) -> Result<State, HVMError>
+where
+    I: IntoIterator<Item = &'a Batch>,
+{
+    let mut state = genesis.clone();
+    for batch in batches {
+        state = apply_batch(&state, batch, programs)?.post_state;
+    }
+    Ok(state)
+}
+
+pub fn collect_deployments<'a>(batch: &'a Batch, outcomes: &[TransactionOutcome]) -> Vec<&'a BendProgram> {
+    let deployed_by_transactions = batch
+        .transactions()
+        .iter()
+        .zip(outcomes)
+        .filter(|(_, outcome)| outcome.success)
+        .filter_map(|(tx, _)| tx.deployed_program());
+    batch.programs().iter().chain(deployed_by_transactions).collect()
+}
+
+fn apply_transaction(
+    state: &mut State,
+    transfers: &mut Vec<Transfer>,
+    tx: &Transaction,
+    batch: &Batch,
+    programs: &HashMap<String, BendProgram>,
+    deployed: &mut HashMap<String, BendProgram>,
+) -> Result<(Vec<u8>, Vec<String>), String> {
+    if tx.chain_id != batch.chain_id() {
+        return Err(format!("Transaction for chain {} in a batch for chain {}", tx.chain_id, batch.chain_id()));
+    }
+    let base_fee = batch.base_fee();
+    let amount = tx.value();
+    let fee = tx
+        .effective_fee(base_fee)
+        .ok_or_else(|| format!("Max fee {} below base fee {}", tx.max_fee, base_fee))?;
+    let debit = amount
+        .checked_add(fee)
+        .ok_or_else(|| format!("Amount plus fee overflows for {}", tx.sender))?;
+    let mut sender = state.get_account(&tx.sender).cloned().unwrap_or_default();
+
+    if tx.nonce != sender.nonce {
+        return Err(format!("Invalid nonce: expected {}, got {}", sender.nonce, tx.nonce));
+    }
+    if sender.balance < debit {
+        return Err(format!("Insufficient balance: {} < {}", sender.balance, debit));
+    }
+    if collides(state, &tx.sender, batch) {
+        return Err(format!("Account {} collides with another account", tx.sender));
+    }
+    if fee > 0 && state.collides(batch.fee_recipient()) {
+        return Err(format!("Fee recipient {} collides with another account", batch.fee_recipient()));
+    }
+
+    let mut logs = Vec::new();
+    let output = match &tx.kind {
+        TransactionKind::Withdraw(_) => {
+            sender.balance -= debit;
+            sender.nonce += 1;
+            transfers.push(Transfer::debit(amount, fee, tx.nonce, write_account(state, &tx.sender, sender)));
+            return Ok((Vec::new(), vec![format!("Withdraw {} from {} to L1 {}", amount, tx.sender, tx.recipient)]));
+        }
+        TransactionKind::DeployProgram(program) => {
+            if programs.contains_key(program.id()) || deployed.contains_key(program.id()) {
+                return Err(format!("Program already deployed: {}", program.id()));
+            }
+            program.check_interface().map_err(|e| e.to_string())?;
+            sender.balance -= debit;
+            sender.nonce += 1;
+            transfers.push(Transfer::debit(amount, fee, tx.nonce, write_account(state, &tx.sender, sender)));
+            deployed.insert(program.id().to_string(), program.as_ref().clone());
+            return Ok((Vec::new(), vec![format!("Deploy {} from {}", program.id(), tx.sender)]));
+        }
+        TransactionKind::Transfer(_) => Vec::new(),
+        TransactionKind::ProgramCall { program_id, calldata, .. } => {
+            let program = programs
+                .get(program_id)
+                .or_else(|| deployed.get(program_id))
+                .ok_or_else(|| format!("Program not found: {}", program_id))?;
+            let result = program.execute(calldata.clone(), PROGRAM_GAS_LIMIT).map_err(|e| e.to_string())?;
+            logs.push(format!("Call {} returned {} outputs", program_id, result.len()));
+            bend::encode_outputs(&result)
+        }
+    };
+
+    if collides(state, &tx.recipient, batch) {
+        return Err(format!("Account {} collides with another account", tx.recipient));
+    }
+    sender.balance -= debit;
+    sender.nonce += 1;
+
+    let mut recipient = if tx.recipient == tx.sender {
+        sender.clone()
+    } else {
+        state.get_account(&tx.recipient).cloned().unwrap_or_default()
+    };
+    recipient.balance = recipient
+        .balance
+        .checked_add(amount)
+        .ok_or_else(|| format!("Balance overflow for {}", tx.recipient))?;
+
+    let debited = write_account(state, &tx.sender, sender);
+    let credited = write_account(state, &tx.recipient, recipient);
+    transfers.push(Transfer::payment(amount, fee, tx.nonce, debited, credited));
+    logs.push(format!("Transfer {} from {} to {}", amount, tx.sender, tx.recipient));
+
+    Ok((output, logs))
+}
+
+fn collides(state: &State, address: &str, batch: &Batch) -> bool {
+    state.collides(address) || merkle::shares_leaf(address, batch.fee_recipient())
+}
+
+fn credit_fees(state: &mut State, transfers: &mut Vec<Transfer>, fee_recipient: &str, fees: u128) -> Result<(), HVMError> {
+    if state.collides(fee_recipient) {
+        return Err(HVMError::ZKRollup(format!("Fee recipient {} collides with another account", fee_recipient)));
+    }
+    let mut recipient = state.get_account(fee_recipient).cloned().unwrap_or_default();
+    recipient.balance = recipient
+        .balance
+        .checked_add(fees)
+        .ok_or_else(|| HVMError::ZKRollup(format!("Fees overflow the balance of {}", fee_recipient)))?;
+    transfers.push(Transfer::fees(fees, write_account(state, fee_recipient, recipient)));
+    Ok(())
+}
+
+fn write_account(state: &mut State, address: &str, account: Account) -> AccountUpdate {
+    let update = AccountUpdate {
+        address: address.to_string(),
+        key: merkle::hash_key(address),
+        old_account: state.get_account(address).cloned().unwrap_or_default(),
+        new_account: account.clone(),
+        siblings: state.siblings(address),
+    };
+    state.set_account(address, account);
+    update
+}
\ No newline at end of file
@@ -0,0 +1,80 @@
+use super::merkle::{self, Hash};
+use super::transition::TransactionOutcome;
+use crate::error::HVMError;
+use crate::sequencer::Batch;
+use serde::{Serialize, Deserialize};
+use sha2::{Sha256, Digest};
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Withdrawal {
+    pub batch_height: u64,
+    pub index: u32,
+    pub sender: String,
+    pub recipient: String,
+    pub amount: u128,
+}
+
+impl Withdrawal {
+    pub fn leaf_hash(&self) -> Hash {
+        let mut hasher = Sha256::new();
+        hasher.update(b"hvm-withdrawal");
+        hasher.update(self.batch_height.to_le_bytes());
+        hasher.update(self.index.to_le_bytes());
+        hasher.update((self.sender.len() as u32).to_le_bytes());
+        hasher.update(self.sender.as_bytes());
+        hasher.update((self.recipient.len() as u32).to_le_bytes());
+        hasher.update(self.recipient.as_bytes());
+        hasher.update(self.amount.to_le_bytes());
+        hasher.finalize().into()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct WithdrawalClaim {
+    pub withdrawal: Withdrawal,
+    #[serde(with = "hex::serde")]
+    pub withdrawal_root: Hash,
+    #[serde(with = "super::encoding::hex_list")]
+    pub path: Vec<Hash>,
+}
+
+pub fn collect_withdrawals(batch: &Batch, outcomes: &[TransactionOutcome]) -> Vec<Withdrawal> {
+    batch
+        .transactions()
+        .iter()
+        .zip(outcomes)
+        .filter(|(tx, outcome)| tx.is_withdrawal() && outcome.success)
+        .enumerate()
+        .map(|(index, (tx, _))| Withdrawal {
+            batch_height: batch.height(),
+            index: index as u32,
+            sender: tx.sender.clone(),
+            recipient: tx.recipient.clone(),
+            amount: tx.value(),
+        })
+        .collect()
+}
+
+pub fn withdrawal_root(withdrawals: &[Withdrawal]) -> Hash {
+    let leaves: Vec<Hash> = withdrawals.iter().map(Withdrawal::leaf_hash).collect();
+    merkle::dense_root(&leaves)
+}
+
+pub fn generate_claim(withdrawals: &[Withdrawal], index: u32) -> Result<WithdrawalClaim, HVMError> {
+    let position = withdrawals
+        .iter()
+        .position(|withdrawal| withdrawal.index == index)
+        .ok_or_else(|| HVMError::ZKRollup(format!("Withdrawal {} not found", index)))?;
+    let leaves: Vec<Hash> = withdrawals.iter().map(Withdrawal::leaf_hash).collect();
+
+    Ok(WithdrawalClaim {
+        withdrawal: withdrawals[position].clone(),
+        withdrawal_root: merkle::dense_root(&leaves),
+        path: merkle::dense_path(&leaves, position),
+    })
+}
+
+pub fn verify_withdrawal_claim(claim: &WithdrawalClaim, trusted_root: &Hash) -> bool {
+    claim.withdrawal_root == *trusted_root
+        && merkle::verify_dense_path(&claim.withdrawal.leaf_hash(), claim.withdrawal.index as usize, &claim.path, trusted_root)
+}
\ No newline at end of file
@@ -0,0 +1,59 @@
+use offchain_labs::zk_rollup::{verify_account_proof, Account, State};
+
+fn funded_state() -> State {
+    let mut state = State::new();
+    state.set_account("Alice", Account::new(1_000, 3));
+    state.set_account("Bob", Account::new(500, 0));
+    state
+}
+
+#[test]
+fn test_inclusion_proof_verifies() {
+    let state = funded_state();
+    let proof = state.prove_account("Alice");
+
+    assert!(proof.is_inclusion());
+    assert_eq!(proof.balance(), 1_000);
+    assert_eq!(proof.nonce(), 3);
+    assert!(verify_account_proof(&proof, &state.root()));
+
+    let json = serde_json::to_string(&proof).unwrap();
+    assert!(verify_account_proof(&serde_json::from_str(&json).unwrap(), &state.root()));
+}
+
+#[test]
+fn test_exclusion_proof_verifies() {
+    let state = funded_state();
+    let proof = state.prove_account("Mallory");
+
+    assert!(!proof.is_inclusion());
+    assert_eq!(proof.balance(), 0);
+    assert!(verify_account_proof(&proof, &state.root()));
+}
+
+#[test]
+fn test_tampered_proof_is_rejected() {
+    let state = funded_state();
+
+    let mut inflated = state.prove_account("Alice");
+    inflated.account = Some(Account::new(1_000_000, 3));
+    assert!(!verify_account_proof(&inflated, &state.root()));
+
+    let mut hidden = state.prove_account("Bob");
+    hidden.account = None;
+    assert!(!verify_account_proof(&hidden, &state.root()), "An existing account cannot be proven absent");
+
+    let mut truncated = state.prove_account("Alice");
+    truncated.siblings.pop();
+    assert!(!verify_account_proof(&truncated, &state.root()));
+}
+
+#[test]
+fn test_proof_is_bound_to_root() {
+    let mut state = funded_state();
+    let stale = state.prove_account("Alice");
+    state.set_account("Alice", Account::new(10, 4));
+
+    assert!(!verify_account_proof(&stale, &state.root()));
+    assert!(verify_account_proof(&state.prove_account("Alice"), &state.root()));
+}
\ No newline at end of file
@@ -0,0 +1,132 @@
+mod common;
+
+use offchain_labs::bend::{BendProgram, ProgramMetadata};
+use offchain_labs::config::{DEV_CHAIN_ID, SequencerConfig};
+use offchain_labs::error::HVMError;
+use offchain_labs::sequencer::{
+    AdmissionContext, AdmissionPipeline, AdmissionValidator, NonceValidator, RejectionReason, Sequencer, Transaction,
+};
+use offchain_labs::zk_rollup::{Account, State};
+use common::{address, signer};
+
+fn signed_transaction(sender: &str, amount: u128, nonce: u64, program_id: &str) -> Transaction {
+    let key = signer(sender);
+    let transaction = if program_id.is_empty() {
+        Transaction::transfer(DEV_CHAIN_ID, key.address(), address("Bob"), amount, nonce)
+    } else {
+        Transaction::call(DEV_CHAIN_ID, key.address(), address("Bob"), program_id.to_string(), Vec::new(), amount, nonce)
+    };
+    transaction.sign(&key)
+}
+
+fn create_test_sequencer() -> Sequencer {
+    let config = SequencerConfig {
+        max_pending_transactions: 10,
+        max_pending_programs: 3,
+        batch_interval_seconds: 1,
+        max_batch_size: 10,
+        max_programs_per_batch: 2,
+        base_fee: 2,
+        max_transaction_bytes: 512,
+        ..Default::default()
+    };
+    let mut state = State::new();
+    state.set_account(&address("Alice"), Account::new(100, 3));
+    Sequencer::new(state, config)
+}
+
+fn rejection(result: Result<(), HVMError>) -> RejectionReason {
+    match result {
+        Err(HVMError::Rejected(reason)) => reason,
+        other => panic!("Expected a typed rejection, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_rejects_stale_nonce_and_unfunded_transfers() {
+    let mut sequencer = create_test_sequencer();
+
+    let stale = signed_transaction("Alice", 1, 2, "").with_fee(2, 0).sign(&signer("Alice"));
+    assert_eq!(rejection(sequencer.process_transaction(stale)), RejectionReason::NonceTooLow { expected: 3, got: 2 });
+
+    let underpriced = signed_transaction("Alice", 1, 3, "").with_fee(1, 0).sign(&signer("Alice"));
+    assert_eq!(
+        rejection(sequencer.process_transaction(underpriced)),
+        RejectionReason::FeeBelowBaseFee { max_fee: 1, base_fee: 2 }
+    );
+
+    let overdrawn = signed_transaction("Alice", 99, 3, "").with_fee(5, 5).sign(&signer("Alice"));
+    assert_eq!(
+        rejection(sequencer.process_transaction(overdrawn)),
+        RejectionReason::InsufficientBalance { required: 104, available: 100 }
+    );
+
+    let affordable = signed_transaction("Alice", 98, 3, "").with_fee(2, 0).sign(&signer("Alice"));
+    assert!(sequencer.process_transaction(affordable).is_ok());
+}
+
+#[test]
+fn test_rejects_unknown_programs_and_oversized_payloads() {
+    let mut sequencer = create_test_sequencer();
+
+    let unknown = signed_transaction("Alice", 1, 3, "missing").with_fee(2, 0).sign(&signer("Alice"));
+    assert_eq!(
+        rejection(sequencer.process_transaction(unknown)),
+        RejectionReason::UnknownProgram("missing".to_string())
+    );
+
+    let metadata = ProgramMetadata { name: "noop".to_string(), version: "1.0".to_string(), description: String::new() };
+    let program = BendProgram::new(vec![0, 97, 115, 109], metadata, "Alice".to_string());
+    let program_id = program.id().to_string();
+    sequencer.deploy_program(program).unwrap();
+    let deployed = signed_transaction("Alice", 1, 3, &program_id).with_fee(2, 0).sign(&signer("Alice"));
+    assert!(sequencer.process_transaction(deployed).is_ok());
+
+    let oversized = signed_transaction("Alice", 1, 4, &"x".repeat(600)).with_fee(2, 0).sign(&signer("Alice"));
+    assert!(matches!(
+        rejection(sequencer.process_transaction(oversized)),
+        RejectionReason::PayloadTooLarge { limit: 512, .. }
+    ));
+}
+
+#[test]
+fn test_rejects_duplicate_transactions() {
+    let mut sequencer = create_test_sequencer();
+    let transaction = signed_transaction("Alice", 1, 4, "").with_fee(2, 0).sign(&signer("Alice"));
+    sequencer.process_transaction(transaction.clone()).unwrap();
+
+    let resigned = transaction.clone().sign(&signer("Alice"));
+    assert_eq!(
+        rejection(sequencer.process_transaction(resigned)),
+        RejectionReason::DuplicateTransaction(transaction.hash())
+    );
+    assert_eq!(sequencer.pending_transactions_count(), 1);
+}
+
+struct DenyRecipient(String);
+
+impl AdmissionValidator for DenyRecipient {
+    fn validate(&self, transaction: &Transaction, _context: &AdmissionContext) -> Result<(), RejectionReason> {
+        if transaction.recipient == self.0 {
+            return Err(RejectionReason::Malformed(format!("Recipient {} is blocked", self.0)));
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_pipeline_accepts_custom_validators() {
+    let mut sequencer = create_test_sequencer();
+    sequencer.set_admission_pipeline(AdmissionPipeline::new().with_validator(NonceValidator));
+    sequencer.add_validator(DenyRecipient(address("Bob")));
+
+    let unfunded = signed_transaction("Charlie", 50, 0, "missing");
+    assert!(matches!(
+        rejection(sequencer.process_transaction(unfunded)),
+        RejectionReason::Malformed(_)
+    ));
+
+    let key = signer("Charlie");
+    let permitted = Transaction::transfer(DEV_CHAIN_ID, key.address(), address("Dave"), 50, 0).sign(&key);
+    assert!(sequencer.process_transaction(permitted).is_ok(), "Balance checks were removed from the pipeline");
+}
\ No newline at end of file
@@ -0,0 +1,76 @@
+mod common;
+
+use offchain_labs::config::DEV_CHAIN_ID;
+use offchain_labs::sequencer::{verify_chain, Batch, L1Deposit, Transaction};
+use offchain_labs::zk_rollup::State;
+use common::{address, create_test_sequencer, seal_and_apply, signer};
+
+#[test]
+fn test_batch_hash_is_deterministic() {
+    let alice = signer("Alice");
+    let transfer = Transaction::transfer(DEV_CHAIN_ID, alice.address(), address("Bob"), 5, 0).sign(&alice);
+    let first = Batch::new(vec![transfer.clone()], vec![]);
+    let second = Batch::new(vec![transfer.clone().sign(&alice)], vec![]);
+    assert_eq!(first.batch_id(), second.batch_id(), "Identity depends only on batch contents");
+    assert_eq!(first.height(), 0);
+    assert_eq!(first.batch_id(), first.header().hash(), "Batches are identified by their header hash");
+
+    let other = Batch::new(vec![transfer.clone(), transfer], vec![]);
+    assert_ne!(first.batch_id(), other.batch_id());
+    assert_ne!(first.batch_id(), first.clone().with_base_fee(1).batch_id());
+}
+
+#[test]
+fn test_sequencer_batches_form_a_chain() {
+    let mut sequencer = create_test_sequencer();
+    sequencer.submit_deposit(L1Deposit::new(1, 0, address("Alice"), 100));
+    let genesis = seal_and_apply(&mut sequencer);
+
+    let alice = signer("Alice");
+    let transfer = Transaction::transfer(DEV_CHAIN_ID, alice.address(), address("Bob"), 40, 0).sign(&alice);
+    sequencer.process_transaction(transfer).unwrap();
+    let next = seal_and_apply(&mut sequencer);
+
+    assert_eq!(genesis.header().parent_hash, [0u8; 32]);
+    assert_eq!(genesis.header().pre_state_root, State::new().root());
+    assert_eq!(next.height(), 1);
+    assert_eq!(sequencer.batch_header(&next.batch_id()), Some(next.header()));
+    assert!(next.header().extends(genesis.header()));
+    assert_eq!(next.header().post_state_root, sequencer.state().root());
+    assert_eq!(sequencer.head(), Some(next.header()));
+    assert!(verify_chain(&[genesis, next]));
+}
+
+#[test]
+fn test_chain_verification_detects_tampering() {
+    let mut sequencer = create_test_sequencer();
+    sequencer.submit_deposit(L1Deposit::new(1, 0, address("Alice"), 100));
+    let first = sequencer.create_batch(true).unwrap().unwrap();
+    sequencer.submit_deposit(L1Deposit::new(2, 0, address("Bob"), 50));
+    let second = sequencer.create_batch(true).unwrap().unwrap();
+    assert!(verify_chain(&[first.clone(), second.clone()]), "Sealed batches chain before they are proven");
+
+    let forged = Batch::with_deposits(vec![L1Deposit::new(2, 0, address("Mallory"), 50)], vec![], vec![])
+        .with_parent(Some(first.header()))
+        .with_state_roots(second.header().pre_state_root, second.header().post_state_root);
+    assert_ne!(forged.batch_id(), second.batch_id());
+    assert!(!verify_chain(&[second.clone(), first.clone()]));
+
+    let mut json = serde_json::to_value(&second).unwrap();
+    json["deposits"][0]["amount"] = serde_json::json!(5_000);
+    let tampered: Batch = serde_json::from_value(json).unwrap();
+    assert!(!verify_chain(&[first, tampered]));
+}
+
+#[test]
+fn test_commit_rejects_batches_off_the_chain() {
+    let mut sequencer = create_test_sequencer();
+    sequencer.submit_deposit(L1Deposit::new(1, 0, address("Alice"), 100));
+    let first = seal_and_apply(&mut sequencer);
+    assert!(sequencer.execute_batch(&first).is_err(), "A batch cannot be committed twice");
+
+    sequencer.revert_batch(&first.batch_id()).unwrap();
+    assert!(sequencer.head().is_none());
+    let resealed = seal_and_apply(&mut sequencer);
+    assert_eq!(resealed.batch_id(), first.batch_id(), "Requeued deposits reseal into the same batch");
+}
\ No newline at end of file
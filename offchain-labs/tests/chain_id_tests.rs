@@ -0,0 +1,95 @@
+mod common;
+
+use offchain_labs::config::SequencerConfig;
+use offchain_labs::Config;
+use offchain_labs::error::HVMError;
+use offchain_labs::sequencer::{Batch, ForcedTransaction, RejectionReason, Sequencer, Transaction};
+use offchain_labs::zk_rollup::{transition, Account, State};
+use std::collections::HashMap;
+use common::{address, signer};
+
+fn transfer(chain_id: u64, nonce: u64) -> Transaction {
+    let key = signer("Alice");
+    Transaction::transfer(chain_id, key.address(), address("Bob"), 10, nonce).sign(&key)
+}
+
+fn create_test_sequencer(chain_id: u64) -> Sequencer {
+    let mut state = State::new();
+    state.set_account(&address("Alice"), Account::new(100, 0));
+    let config = SequencerConfig {
+        chain_id,
+        max_pending_transactions: 10,
+        max_pending_programs: 3,
+        batch_interval_seconds: 1,
+        max_batch_size: 10,
+        max_programs_per_batch: 2,
+        ..Default::default()
+    };
+    Sequencer::new(state, config)
+}
+
+#[test]
+fn test_admission_rejects_transactions_for_other_chains() {
+    let mut sequencer = create_test_sequencer(7);
+    match sequencer.process_transaction(transfer(1, 0)) {
+        Err(HVMError::Rejected(reason)) => assert_eq!(reason, RejectionReason::WrongChain { expected: 7, got: 1 }),
+        other => panic!("Expected a rejection, got {:?}", other),
+    }
+    assert!(!sequencer.submit_forced(ForcedTransaction::new(1, 0, transfer(1, 0))), "Forced transactions are replay protected too");
+
+    sequencer.process_transaction(transfer(7, 0)).unwrap();
+    assert_eq!(sequencer.pending_transactions_count(), 1);
+}
+
+#[test]
+fn test_chain_id_is_covered_by_signature_and_hash() {
+    let transaction = transfer(1, 0);
+    let mut replayed = transaction.clone();
+    replayed.chain_id = 2;
+
+    assert!(transaction.verify_signature().is_ok());
+    assert!(replayed.verify_signature().is_err());
+    assert_ne!(replayed.hash(), transaction.hash());
+    assert_eq!(Transaction::decode(&transaction.encode()).unwrap().chain_id, 1);
+}
+
+#[test]
+fn test_batch_headers_carry_the_chain_id() {
+    let mut mainnet = create_test_sequencer(7);
+    mainnet.process_transaction(transfer(7, 0)).unwrap();
+    let batch = mainnet.create_batch(true).unwrap().unwrap();
+    assert_eq!(batch.chain_id(), 7);
+    assert_ne!(batch.clone().with_chain_id(8).batch_id(), batch.batch_id());
+
+    let mut testnet = create_test_sequencer(8);
+    let error = testnet.execute_batch(&batch).unwrap_err();
+    assert!(error.to_string().contains("belongs to chain 7"));
+    assert_eq!(testnet.state().root(), create_test_sequencer(8).state().root());
+}
+
+#[test]
+fn test_transition_fails_transactions_from_other_chains() {
+    let mut state = State::new();
+    state.set_account(&address("Alice"), Account::new(100, 0));
+    let batch = Batch::new(vec![transfer(1, 0), transfer(7, 0)], vec![]).with_chain_id(7);
+
+    let transition = transition::apply_batch(&state, &batch, &HashMap::new()).unwrap();
+    assert!(!transition.outcomes[0].success);
+    assert!(transition.outcomes[1].success);
+    assert_eq!(transition.post_state.get_account(&address("Bob")).unwrap().balance, 10);
+}
+#[test]
+fn test_config_requires_a_chain_id() {
+    assert!(SequencerConfig::default().validate().is_ok());
+
+    let unnamed: SequencerConfig = serde_json::from_str(r#"{ "max_batch_size": 10 }"#).unwrap();
+    assert_eq!(unnamed.chain_id, 0, "A config file must name its chain");
+    assert!(unnamed.validate().is_err());
+
+    let named: SequencerConfig = serde_json::from_str(r#"{ "chain_id": 7 }"#).unwrap();
+    assert!(named.validate().is_ok());
+
+    let mut config = serde_json::to_value(Config::default()).unwrap();
+    config["sequencer_config"].as_object_mut().unwrap().remove("chain_id");
+    assert!(serde_json::from_value::<Config>(config).unwrap().validate().is_err(), "A missing chain id must not fall back to the dev chain");
+}
@@ -0,0 +1,115 @@
+use offchain_labs::config::DEV_CHAIN_ID;
+use offchain_labs::sequencer::{Batch, Transaction};
+use offchain_labs::zk_rollup::circuit::{self, StateTransitionCircuit};
+use offchain_labs::zk_rollup::{transition, Account, State};
+use ark_bn254::Fr;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystem};
+use std::collections::HashMap;
+
+fn transfer_circuit() -> StateTransitionCircuit {
+    let mut state = State::new();
+    state.set_account("Alice", Account::new(1_000, 0));
+    let batch = Batch::new(vec![
+        Transaction::transfer(DEV_CHAIN_ID, "Alice".to_string(), "Bob".to_string(), 100, 0),
+    ], vec![]).with_chain_id(DEV_CHAIN_ID);
+
+    let transition = transition::apply_batch(&state, &batch, &HashMap::new()).unwrap();
+    assert_eq!(transition.transfers.len(), 1);
+    StateTransitionCircuit::new(circuit::capacity_for(1), &transition, batch.batch_id()).unwrap()
+}
+
+fn paid_transfer_circuit() -> StateTransitionCircuit {
+    let mut state = State::new();
+    state.set_account("Alice", Account::new(1_000, 0));
+    let batch = Batch::new(vec![
+        Transaction::transfer(DEV_CHAIN_ID, "Alice".to_string(), "Alice".to_string(), 100, 0).with_fee(7, 2),
+    ], vec![]).with_chain_id(DEV_CHAIN_ID).with_base_fee(5);
+
+    let transition = transition::apply_batch(&state, &batch, &HashMap::new()).unwrap();
+    assert_eq!(transition.transfers.len(), 2, "The self-transfer and the fee credit");
+    StateTransitionCircuit::new(circuit::capacity_for(1), &transition, batch.batch_id()).unwrap()
+}
+
+fn is_satisfied(circuit: StateTransitionCircuit) -> bool {
+    let cs = ConstraintSystem::<Fr>::new_ref();
+    circuit.generate_constraints(cs.clone()).unwrap();
+    cs.is_satisfied().unwrap()
+}
+
+#[test]
+fn test_transfer_circuit_is_satisfied() {
+    let circuit = transfer_circuit();
+    let cs = ConstraintSystem::<Fr>::new_ref();
+    circuit.generate_constraints(cs.clone()).unwrap();
+    assert!(cs.is_satisfied().unwrap());
+    assert_eq!(
+        cs.num_instance_variables(),
+        6,
+        "Expected old root, new root, withdrawals root, batch digest and batch commitment as public inputs"
+    );
+}
+
+#[test]
+fn test_circuit_rejects_wrong_new_root() {
+    let mut circuit = transfer_circuit();
+    circuit.new_root = circuit.old_root;
+    let cs = ConstraintSystem::<Fr>::new_ref();
+    circuit.generate_constraints(cs.clone()).unwrap();
+    assert!(!cs.is_satisfied().unwrap());
+}
+
+#[test]
+fn test_circuit_rejects_forged_amount() {
+    let mut circuit = transfer_circuit();
+    circuit.transfers[0].amount += 1;
+    assert!(!is_satisfied(circuit));
+}
+
+#[test]
+fn test_circuit_rejects_unpaired_credit() {
+    let mut circuit = transfer_circuit();
+    circuit.transfers[0].debit = None;
+    assert!(!is_satisfied(circuit));
+}
+
+#[test]
+fn test_circuit_rejects_wrong_sender_nonce() {
+    let mut circuit = transfer_circuit();
+    circuit.transfers[0].nonce += 1;
+    assert!(!is_satisfied(circuit));
+}
+
+#[test]
+fn test_circuit_charges_fees_to_the_sender() {
+    assert!(is_satisfied(paid_transfer_circuit()));
+
+    let mut circuit = paid_transfer_circuit();
+    circuit.transfers[0].fee -= 1;
+    assert!(!is_satisfied(circuit));
+
+    let mut circuit = paid_transfer_circuit();
+    circuit.transfers[1].amount += 1;
+    assert!(!is_satisfied(circuit), "The fee credit must equal the fees debited in the batch");
+}
+
+#[test]
+fn test_capacity_is_enforced() {
+    let state = State::new();
+    let batch = Batch::new(vec![
+        Transaction::transfer(DEV_CHAIN_ID, "Alice".to_string(), "Bob".to_string(), 0, 0),
+    ], vec![]).with_chain_id(DEV_CHAIN_ID);
+    let transition = transition::apply_batch(&state, &batch, &HashMap::new()).unwrap();
+    assert!(StateTransitionCircuit::new(0, &transition, batch.batch_id()).is_err());
+}
+
+#[test]
+fn test_constraints_per_transfer_matches_synthesis() {
+    let fixed = ConstraintSystem::<Fr>::new_ref();
+    StateTransitionCircuit::blank(0).generate_constraints(fixed.clone()).unwrap();
+    let cs = ConstraintSystem::<Fr>::new_ref();
+    transfer_circuit().generate_constraints(cs.clone()).unwrap();
+    assert_eq!(
+        cs.num_constraints() as u64,
+        fixed.num_constraints() as u64 + circuit::constraints_for(circuit::capacity_for(1))
+    );
+}
\ No newline at end of file
@@ -0,0 +1,52 @@
+#![allow(dead_code)]
+
+use offchain_labs::config::{DEV_CHAIN_ID, SequencerConfig};
+use offchain_labs::sequencer::{Batch, Sequencer, Transaction, TransactionSigner};
+use offchain_labs::zk_rollup::{Account, State};
+use sha2::{Digest, Sha256};
+
+pub fn signer(name: &str) -> TransactionSigner {
+    TransactionSigner::sr25519_from_seed(&Sha256::digest(name.as_bytes()).into()).unwrap()
+}
+
+pub fn address(name: &str) -> String {
+    signer(name).address()
+}
+
+pub fn signed_transaction(sender: &str, recipient: &str, amount: u128, nonce: u64) -> Transaction {
+    let key = signer(sender);
+    Transaction::transfer(DEV_CHAIN_ID, key.address(), signer(recipient).address(), amount, nonce).sign(&key)
+}
+
+pub fn test_config() -> SequencerConfig {
+    SequencerConfig {
+        max_pending_transactions: 10,
+        max_pending_programs: 3,
+        batch_interval_seconds: 1,
+        max_batch_size: 10,
+        max_programs_per_batch: 2,
+        ..Default::default()
+    }
+}
+
+pub fn funded_state(names: &[&str], balance: u128) -> State {
+    let mut state = State::new();
+    for name in names {
+        state.set_account(&address(name), Account::new(balance, 0));
+    }
+    state
+}
+
+pub fn create_test_sequencer() -> Sequencer {
+    Sequencer::new(State::new(), test_config())
+}
+
+pub fn apply(sequencer: &mut Sequencer, batch: &Batch) {
+    sequencer.execute_batch(batch).unwrap();
+}
+
+pub fn seal_and_apply(sequencer: &mut Sequencer) -> Batch {
+    let batch = sequencer.create_batch(true).unwrap().unwrap();
+    apply(sequencer, &batch);
+    batch
+}
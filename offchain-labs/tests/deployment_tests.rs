@@ -0,0 +1,87 @@
+mod common;
+
+use offchain_labs::bend::{BendProgram, ProgramMetadata};
+use offchain_labs::config::DEV_CHAIN_ID;
+use offchain_labs::sequencer::{Sequencer, Transaction, TransactionStatus};
+use common::{apply, funded_state, signer, test_config};
+
+fn program(name: &str) -> BendProgram {
+    let metadata = ProgramMetadata { name: name.to_string(), version: "1.0".to_string(), description: String::new() };
+    BendProgram::new([0, 97, 115, 109].iter().copied().chain(name.bytes()).collect(), metadata, "Alice".to_string())
+}
+
+fn call(program_id: &str) -> Transaction {
+    let key = signer("Alice");
+    Transaction::call(DEV_CHAIN_ID, key.address(), key.address(), program_id.to_string(), vec![1], 0, 0).sign(&key)
+}
+
+fn create_test_sequencer() -> Sequencer {
+    Sequencer::new(funded_state(&["Alice"], 100), test_config())
+}
+
+#[test]
+fn test_program_is_callable_only_after_its_batch_is_applied() {
+    let mut sequencer = create_test_sequencer();
+    let program = program("counter");
+    let program_id = program.id().to_string();
+    sequencer.submit_program(program).unwrap();
+    assert_eq!(sequencer.deployment_receipt(&program_id).unwrap().status, TransactionStatus::Pending);
+    assert!(sequencer.process_transaction(call(&program_id)).is_err());
+
+    let batch = sequencer.create_batch(true).unwrap().unwrap();
+    assert_eq!(batch.programs().len(), 1);
+    assert_eq!(sequencer.deployment_receipt(&program_id).unwrap().status, TransactionStatus::Batched);
+    assert!(sequencer.process_transaction(call(&program_id)).is_err(), "Sealed but unproven programs are not callable");
+
+    apply(&mut sequencer, &batch);
+    let receipt = sequencer.deployment_receipt(&program_id).unwrap();
+    assert_eq!(receipt.status, TransactionStatus::Proven);
+    assert_eq!(receipt.batch_id, Some(batch.batch_id()));
+    assert_eq!(receipt.program_id, program_id);
+    assert_eq!(sequencer.pending_programs_count(), 0);
+    assert_eq!(sequencer.processed_programs_count(), 1);
+    assert!(sequencer.process_transaction(call(&program_id)).is_ok());
+}
+
+#[test]
+fn test_duplicate_program_submissions_are_rejected() {
+    let mut sequencer = create_test_sequencer();
+    sequencer.submit_program(program("counter")).unwrap();
+    assert!(sequencer.submit_program(program("counter")).is_err());
+
+    let batch = sequencer.create_batch(true).unwrap().unwrap();
+    apply(&mut sequencer, &batch);
+    assert!(sequencer.submit_program(program("counter")).is_err(), "Deployed programs cannot be submitted again");
+    assert!(sequencer.submit_program(program("ledger")).is_ok());
+}
+
+#[test]
+fn test_deployment_receipt_follows_batch_lifecycle() {
+    let mut sequencer = create_test_sequencer();
+    let program = program("counter");
+    let program_id = program.id().to_string();
+    sequencer.submit_program(program).unwrap();
+    let batch = sequencer.create_batch(true).unwrap().unwrap();
+    apply(&mut sequencer, &batch);
+
+    sequencer.mark_batch_submitted(&batch.batch_id()).unwrap();
+    sequencer.mark_batch_finalized(&batch.batch_id()).unwrap();
+    assert_eq!(sequencer.deployment_receipt(&program_id).unwrap().status, TransactionStatus::Finalized);
+    assert_eq!(sequencer.batch_deployments(&batch.batch_id()).len(), 1);
+}
+
+#[test]
+fn test_reverting_a_deployment_batch_undeploys_the_program() {
+    let mut sequencer = create_test_sequencer();
+    let program = program("counter");
+    let program_id = program.id().to_string();
+    sequencer.submit_program(program).unwrap();
+    let batch = sequencer.create_batch(true).unwrap().unwrap();
+    apply(&mut sequencer, &batch);
+
+    sequencer.revert_batch(&batch.batch_id()).unwrap();
+    assert!(!sequencer.deployed_programs().contains_key(&program_id));
+    assert_eq!(sequencer.processed_programs_count(), 0);
+    assert!(matches!(&sequencer.deployment_receipt(&program_id).unwrap().status, TransactionStatus::Failed(reason) if reason.contains("reverted")));
+    assert!(sequencer.process_transaction(call(&program_id)).is_err());
+}
\ No newline at end of file
@@ -0,0 +1,83 @@
+mod common;
+
+use offchain_labs::config::DEV_CHAIN_ID;
+use offchain_labs::sequencer::{DepositQueue, L1Deposit, MockDepositSource, Transaction};
+use offchain_labs::zk_rollup::{merkle, Account};
+use common::{address, create_test_sequencer, seal_and_apply, signer};
+
+#[test]
+fn test_deposit_queue_is_exactly_once() {
+    let mut queue = DepositQueue::new();
+    assert!(queue.enqueue(L1Deposit::new(10, 0, "Alice".to_string(), 100)));
+    assert!(queue.enqueue(L1Deposit::new(10, 1, "Bob".to_string(), 50)));
+    assert!(!queue.enqueue(L1Deposit::new(10, 1, "Bob".to_string(), 50)), "Duplicate event must be ignored");
+    assert!(!queue.enqueue(L1Deposit::new(9, 7, "Mallory".to_string(), 1)), "Older event must be ignored");
+    assert!(queue.enqueue(L1Deposit::new(11, 0, "Alice".to_string(), 25)));
+
+    assert_eq!(queue.len(), 3);
+    assert_eq!(queue.cursor().map(|cursor| (cursor.block_number, cursor.event_index)), Some((11, 0)));
+}
+
+#[test]
+fn test_ingested_deposits_are_credited_once() {
+    let mut sequencer = create_test_sequencer();
+    let mut source = MockDepositSource::new();
+    source.push(L1Deposit::new(10, 1, address("Bob"), 50));
+    source.push(L1Deposit::new(10, 0, address("Alice"), 100));
+
+    assert_eq!(sequencer.ingest_deposits(&mut source).unwrap(), 2);
+    assert_eq!(sequencer.ingest_deposits(&mut source).unwrap(), 0, "Polling again must not requeue deposits");
+
+    let batch = sequencer.create_batch(true).unwrap().unwrap();
+    assert_eq!(batch.deposits().iter().map(|deposit| deposit.event_index).collect::<Vec<_>>(), vec![0, 1]);
+    sequencer.execute_batch(&batch).unwrap();
+
+    let alice = signer("Alice");
+    let transfer = Transaction::transfer(DEV_CHAIN_ID, alice.address(), address("Bob"), 30, 0).sign(&alice);
+    sequencer.process_transaction(transfer).unwrap();
+    source.push(L1Deposit::new(12, 0, address("Alice"), 5));
+    assert_eq!(sequencer.ingest_deposits(&mut source).unwrap(), 1);
+    seal_and_apply(&mut sequencer);
+
+    assert_eq!(sequencer.state().get_account(&address("Alice")), Some(&Account::new(75, 1)));
+    assert_eq!(sequencer.state().get_account(&address("Bob")), Some(&Account::new(80, 0)));
+    assert_eq!(sequencer.pending_deposits_count(), 0);
+}
+
+#[test]
+fn test_reverted_deposits_are_requeued() {
+    let mut sequencer = create_test_sequencer();
+    sequencer.submit_deposit(L1Deposit::new(1, 0, address("Alice"), 100));
+    let first_id = seal_and_apply(&mut sequencer).batch_id();
+    sequencer.submit_deposit(L1Deposit::new(2, 0, address("Bob"), 40));
+    seal_and_apply(&mut sequencer);
+
+    sequencer.revert_batch(&first_id).unwrap();
+    assert_eq!(sequencer.pending_deposits_count(), 2);
+    assert!(sequencer.state().get_account(&address("Alice")).is_none());
+
+    seal_and_apply(&mut sequencer);
+    assert_eq!(sequencer.state().get_account(&address("Alice")), Some(&Account::new(100, 0)));
+    assert_eq!(sequencer.state().get_account(&address("Bob")), Some(&Account::new(40, 0)));
+    assert!(!sequencer.submit_deposit(L1Deposit::new(1, 0, address("Alice"), 100)));
+}
+#[test]
+fn test_rejected_deposits_are_recorded_in_the_batch() {
+    let mut sequencer = create_test_sequencer();
+    let credited = L1Deposit::new(1, 0, address("Carol"), u128::MAX);
+    let overflowing = L1Deposit::new(1, 1, address("Carol"), 1);
+    sequencer.submit_deposit(credited);
+    sequencer.submit_deposit(overflowing.clone());
+
+    let batch = seal_and_apply(&mut sequencer);
+    assert_eq!(batch.rejected_deposits(), &[overflowing.id()]);
+    assert!(batch.verify_contents());
+    let header = sequencer.batch_header(&batch.batch_id()).unwrap();
+    assert_eq!(header.rejected_deposits_root, merkle::dense_root(&[overflowing.leaf_hash()]));
+    assert_eq!(sequencer.rejected_deposits(&batch.batch_id()), &[overflowing.id()]);
+    assert_eq!(sequencer.state().get_account(&address("Carol")), Some(&Account::new(u128::MAX, 0)));
+    assert_eq!(sequencer.pending_deposits_count(), 0);
+
+    let forged = batch.clone().with_rejected_deposits(Vec::new());
+    assert_ne!(forged.batch_id(), batch.batch_id(), "Dropping a rejection changes the batch id");
+}
@@ -0,0 +1,95 @@
+mod common;
+
+use offchain_labs::config::DEV_CHAIN_ID;
+use offchain_labs::sequencer::{L1Deposit, Sequencer, Transaction};
+use offchain_labs::zk_rollup::diff::rebuild_state;
+use offchain_labs::zk_rollup::{Account, State, StateDiff};
+use common::{address, create_test_sequencer, funded_state, seal_and_apply, signed_transaction, signer, test_config};
+
+#[test]
+fn test_state_diff_binary_roundtrip() {
+    let pre_state = State::new();
+    let mut post_state = pre_state.clone();
+    post_state.set_account("Alice", Account::new(u128::MAX, 9));
+    post_state.set_account("Bob", Account::new(1, 0));
+
+    let diff = StateDiff { batch_id: [3u8; 32], pre_root: pre_state.root(), post_root: post_state.root(), accounts: pre_state.diff(&post_state) };
+    let decoded = StateDiff::from_bytes(&diff.to_bytes()).unwrap();
+    assert_eq!(decoded, diff);
+
+    let bytes = diff.to_bytes();
+    assert!(StateDiff::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+    assert!(StateDiff::from_bytes(&[b"HVMX".as_slice(), &bytes[4..]].concat()).is_err());
+}
+
+#[test]
+fn test_state_diff_json_encodes_hashes_as_hex() {
+    let mut post_state = State::new();
+    post_state.set_account("Alice", Account::new(7, 1));
+    let diff = StateDiff { batch_id: [3u8; 32], pre_root: State::new().root(), post_root: post_state.root(), accounts: State::new().diff(&post_state) };
+
+    let json: serde_json::Value = serde_json::to_value(&diff).unwrap();
+    assert_eq!(json["batch_id"], hex::encode([3u8; 32]));
+    assert_eq!(json["pre_root"], hex::encode(diff.pre_root));
+    assert_eq!(json["post_root"], hex::encode(diff.post_root));
+    assert_eq!(json["accounts"][0][1]["storage_root"], hex::encode([0u8; 32]));
+    assert_eq!(serde_json::from_value::<StateDiff>(json).unwrap(), diff);
+}
+
+#[test]
+fn test_rebuild_state_from_diffs() {
+    let mut sequencer = create_test_sequencer();
+    sequencer.submit_deposit(L1Deposit::new(1, 0, address("Alice"), 1_000));
+    let first = seal_and_apply(&mut sequencer).batch_id();
+    let alice = signer("Alice");
+    let bob = signer("Bob");
+    sequencer.process_transaction(Transaction::transfer(DEV_CHAIN_ID, alice.address(), bob.address(), 200, 0).sign(&alice)).unwrap();
+    let second = seal_and_apply(&mut sequencer).batch_id();
+    sequencer.process_transaction(Transaction::withdrawal(DEV_CHAIN_ID, alice.address(), "0xa11ce".to_string(), 800, 1).sign(&alice)).unwrap();
+    sequencer.process_transaction(Transaction::transfer(DEV_CHAIN_ID, bob.address(), address("Charlie"), 200, 0).sign(&bob)).unwrap();
+    let third = seal_and_apply(&mut sequencer).batch_id();
+
+    let encoded: Vec<Vec<u8>> = [first, second, third]
+        .iter()
+        .map(|batch_id| sequencer.state_diff(batch_id).unwrap().to_bytes())
+        .collect();
+    let diffs: Vec<StateDiff> = encoded.iter().map(|bytes| StateDiff::from_bytes(bytes).unwrap()).collect();
+
+    let rebuilt = rebuild_state(&State::new(), &diffs).unwrap();
+    assert_eq!(rebuilt.root(), sequencer.state().root());
+    assert_eq!(rebuilt, *sequencer.state());
+    assert_eq!(rebuilt.get_account(&address("Alice")), Some(&Account::new(0, 2)));
+    assert_eq!(rebuilt.get_account(&address("Charlie")), Some(&Account::new(200, 0)));
+}
+
+#[test]
+fn test_diffs_must_be_applied_in_order() {
+    let mut sequencer = create_test_sequencer();
+    sequencer.submit_deposit(L1Deposit::new(1, 0, address("Alice"), 10));
+    let first = seal_and_apply(&mut sequencer).batch_id();
+    sequencer.submit_deposit(L1Deposit::new(2, 0, address("Alice"), 20));
+    let second = seal_and_apply(&mut sequencer).batch_id();
+
+    let diffs = vec![sequencer.state_diff(&second).unwrap().clone(), sequencer.state_diff(&first).unwrap().clone()];
+    assert!(rebuild_state(&State::new(), &diffs).is_err());
+
+    let mut tampered = sequencer.state_diff(&first).unwrap().clone();
+    tampered.accounts[0].1.balance += 1;
+    assert!(rebuild_state(&State::new(), &[tampered]).is_err());
+}
+#[test]
+fn test_state_diff_lists_only_changed_accounts() {
+    let mut genesis = funded_state(&["Alice", "Carol"], 100);
+    for index in 0..20 {
+        genesis.set_account(&format!("bystander-{}", index), Account::new(1, 0));
+    }
+    let mut sequencer = Sequencer::new(genesis.clone(), test_config());
+    sequencer.process_transaction(signed_transaction("Alice", "Bob", 30, 0)).unwrap();
+    let batch_id = seal_and_apply(&mut sequencer).batch_id();
+
+    let diff = sequencer.state_diff(&batch_id).unwrap();
+    let mut expected = vec![(address("Alice"), Account::new(70, 1)), (address("Bob"), Account::new(30, 0))];
+    expected.sort_by(|a, b| a.0.cmp(&b.0));
+    assert_eq!(diff.accounts, expected);
+    assert_eq!(rebuild_state(&genesis, [diff]).unwrap(), *sequencer.state());
+}
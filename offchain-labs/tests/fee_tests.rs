@@ -0,0 +1,106 @@
+mod common;
+
+use offchain_labs::config::{DEV_CHAIN_ID, SequencerConfig};
+use offchain_labs::bend::{BendProgram, ProgramMetadata};
+use offchain_labs::sequencer::{L1Deposit, Mempool, Sequencer, Transaction, TransactionStatus};
+use offchain_labs::zk_rollup::{Account, State};
+use common::{address, signer};
+
+fn fee_transaction(sender: &str, amount: u128, nonce: u64, max_fee: u128, priority_tip: u128) -> Transaction {
+    let key = signer(sender);
+    Transaction::transfer(DEV_CHAIN_ID, key.address(), address("Treasury"), amount, nonce)
+        .with_fee(max_fee, priority_tip)
+        .sign(&key)
+}
+
+fn create_test_sequencer(state: State, base_fee: u128) -> Sequencer {
+    let config = SequencerConfig {
+        max_pending_transactions: 10,
+        max_pending_programs: 3,
+        batch_interval_seconds: 1,
+        max_batch_size: 10,
+        max_programs_per_batch: 2,
+        base_fee,
+        fee_recipient: address("Sequencer"),
+        ..Default::default()
+    };
+    Sequencer::new(state, config)
+}
+
+#[test]
+fn test_effective_fee_is_capped_and_signed() {
+    let tx = fee_transaction("Alice", 10, 0, 30, 20);
+    assert_eq!(tx.effective_fee(5), Some(25));
+    assert_eq!(tx.effective_fee(15), Some(30));
+    assert_eq!(tx.effective_fee(31), None);
+
+    let mut bumped = tx.clone();
+    bumped.priority_tip = 1_000;
+    assert!(tx.verify_signature().is_ok());
+    assert!(bumped.verify_signature().is_err(), "Fee fields must be covered by the signature");
+}
+
+#[test]
+fn test_take_ready_orders_by_fee_within_nonce_order() {
+    let mut mempool = Mempool::new();
+    mempool.insert(fee_transaction("Alice", 1, 0, 1, 1), 0).unwrap();
+    mempool.insert(fee_transaction("Alice", 1, 1, 100, 100), 0).unwrap();
+    mempool.insert(fee_transaction("Bob", 1, 0, 50, 50), 0).unwrap();
+    mempool.insert(fee_transaction("Charlie", 1, 0, 10, 10), 0).unwrap();
+
+    let taken: Vec<(String, u64)> = mempool.take_ready(4, 0).into_iter().map(|tx| (tx.sender, tx.nonce)).collect();
+    assert_eq!(taken, vec![
+        (address("Bob"), 0),
+        (address("Charlie"), 0),
+        (address("Alice"), 0),
+        (address("Alice"), 1),
+    ]);
+}
+
+#[test]
+fn test_sequencer_charges_fees_and_reverts_revenue() {
+    let mut state = State::new();
+    state.set_account(&address("Alice"), Account::new(1_000, 0));
+    let mut sequencer = create_test_sequencer(state, 5);
+
+    assert!(sequencer.process_transaction(fee_transaction("Alice", 100, 0, 4, 10)).is_err());
+    sequencer.process_transaction(fee_transaction("Alice", 100, 0, 20, 10)).unwrap();
+
+    let batch = sequencer.create_batch(true).unwrap().unwrap();
+    assert_eq!(batch.base_fee(), 5);
+    let outcomes = sequencer.execute_batch(&batch).unwrap();
+    assert!(outcomes[0].success);
+
+    assert_eq!(sequencer.state().get_account(&address("Alice")).unwrap().balance, 1_000 - 100 - 15);
+    assert_eq!(sequencer.state().get_account(&address("Treasury")).unwrap().balance, 100);
+    assert_eq!(sequencer.state().get_account(&address("Sequencer")).unwrap().balance, 15);
+    assert_eq!(sequencer.collected_fees(), 15);
+    assert_eq!(batch.header().post_state_root, sequencer.state().root(), "The fee credit is part of the proven transition");
+
+    sequencer.revert_batch(&batch.batch_id()).unwrap();
+    assert_eq!(sequencer.collected_fees(), 0);
+    assert!(sequencer.state().get_account(&address("Sequencer")).is_none());
+}
+#[test]
+fn test_failed_sealing_restores_drained_contents() {
+    let mut state = State::new();
+    state.set_account(&address("Alice"), Account::new(1_000, 0));
+    state.set_account(&address("Sequencer"), Account::new(u128::MAX, 0));
+    let mut sequencer = create_test_sequencer(state, 5);
+
+    let first = fee_transaction("Alice", 100, 0, 20, 10);
+    sequencer.process_transaction(first.clone()).unwrap();
+    sequencer.process_transaction(fee_transaction("Alice", 100, 1, 20, 10)).unwrap();
+    sequencer.submit_deposit(L1Deposit::new(1, 0, address("Bob"), 50));
+    let metadata = ProgramMetadata { name: "counter".to_string(), version: "1.0".to_string(), description: String::new() };
+    sequencer.submit_program(BendProgram::new(vec![0, 97, 115, 109], metadata, address("Alice"))).unwrap();
+
+    assert!(sequencer.create_batch(true).is_err(), "Crediting the fees overflows the fee recipient");
+    assert_eq!(sequencer.pending_transactions_count(), 2);
+    assert_eq!(sequencer.ready_transactions_count(), 2);
+    assert_eq!(sequencer.pending_deposits_count(), 1);
+    assert_eq!(sequencer.pending_programs_count(), 1);
+    assert_eq!(sequencer.receipt(&first.hash()).unwrap().status, TransactionStatus::Pending);
+    assert!(sequencer.create_batch(true).is_err(), "The restored contents are selected again");
+    assert_eq!(sequencer.pending_transactions_count(), 2);
+}
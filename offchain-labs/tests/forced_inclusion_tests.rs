@@ -0,0 +1,118 @@
+mod common;
+
+use offchain_labs::config::{DEV_CHAIN_ID, SequencerConfig};
+use offchain_labs::sequencer::{
+    AdmissionContext, AdmissionValidator, ForcedTransaction, MockForcedInclusionSource, RejectionReason, Sequencer,
+    Transaction, TransactionKind, TransactionStatus,
+};
+use common::{address, apply, funded_state, signer, test_config};
+
+fn transfer(sender: &str, nonce: u64) -> Transaction {
+    let key = signer(sender);
+    Transaction::transfer(DEV_CHAIN_ID, key.address(), address("Bob"), 1, nonce).sign(&key)
+}
+
+fn create_test_sequencer(window: u64) -> Sequencer {
+    Sequencer::new(funded_state(&["Alice", "Carol"], 100), SequencerConfig { forced_inclusion_window: window, ..test_config() })
+}
+
+struct DenySender(String);
+
+impl AdmissionValidator for DenySender {
+    fn validate(&self, transaction: &Transaction, _context: &AdmissionContext) -> Result<(), RejectionReason> {
+        if transaction.sender == self.0 {
+            return Err(RejectionReason::Malformed(format!("Sender {} is censored", self.0)));
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_forced_transactions_lead_the_next_batch() {
+    let mut sequencer = create_test_sequencer(4);
+    sequencer.process_transaction(transfer("Carol", 0)).unwrap();
+    let forced = transfer("Alice", 0);
+    assert!(sequencer.submit_forced(ForcedTransaction::new(10, 0, forced.clone())));
+    assert!(!sequencer.submit_forced(ForcedTransaction::new(10, 0, forced.clone())), "Replayed L1 message must be ignored");
+
+    let batch = sequencer.create_batch(true).unwrap().unwrap();
+    assert_eq!(batch.transactions().len(), 2);
+    assert_eq!(batch.transactions()[0].hash(), forced.hash());
+    apply(&mut sequencer, &batch);
+
+    assert_eq!(sequencer.forced_transactions_count(), 0);
+    assert_eq!(sequencer.receipt(&forced.hash()).unwrap().status, TransactionStatus::Proven);
+    assert_eq!(sequencer.state().get_account(&address("Alice")).unwrap().nonce, 1);
+}
+
+#[test]
+fn test_forced_transactions_bypass_admission() {
+    let mut sequencer = create_test_sequencer(4);
+    sequencer.add_validator(DenySender(address("Alice")));
+    assert!(sequencer.process_transaction(transfer("Alice", 0)).is_err());
+
+    let forced = transfer("Alice", 0);
+    assert!(sequencer.submit_forced(ForcedTransaction::new(3, 1, forced.clone())));
+    assert!(sequencer.has_pending_work(), "An empty mempool must not delay forced transactions");
+
+    let batch = sequencer.create_batch(true).unwrap().unwrap();
+    assert_eq!(batch.transactions().len(), 1);
+    assert_eq!(batch.transactions()[0].hash(), forced.hash());
+    apply(&mut sequencer, &batch);
+    assert_eq!(sequencer.state().get_account(&address("Bob")).unwrap().balance, 1);
+}
+
+#[test]
+fn test_batch_skipping_overdue_forced_transaction_is_rejected() {
+    let mut censoring = create_test_sequencer(1);
+    let mut honest = create_test_sequencer(1);
+    censoring.process_transaction(transfer("Carol", 0)).unwrap();
+    let censored = censoring.create_batch(true).unwrap().unwrap();
+
+    let forced = transfer("Alice", 0);
+    assert!(honest.submit_forced(ForcedTransaction::new(1, 0, forced.clone())));
+    let error = honest.execute_batch(&censored).unwrap_err();
+    assert!(error.to_string().contains(&hex::encode(forced.hash())));
+    assert_eq!(honest.forced_transactions_count(), 1);
+
+    let batch = honest.create_batch(true).unwrap().unwrap();
+    assert_eq!(batch.transactions()[0].hash(), forced.hash());
+    apply(&mut honest, &batch);
+    assert_eq!(honest.forced_transactions_count(), 0);
+}
+
+#[test]
+fn test_forced_flood_is_spread_over_batches_that_can_include_it() {
+    let mut sequencer = create_test_sequencer(1);
+    let senders = ["Alice", "Carol", "Dave", "Erin", "Frank", "Grace", "Heidi", "Ivan", "Judy", "Mallory", "Niaj", "Olivia"];
+    for (index, sender) in senders.iter().enumerate() {
+        assert!(sequencer.submit_forced(ForcedTransaction::new(1, index as u32, transfer(sender, 0))));
+    }
+
+    let mut included = Vec::new();
+    while let Some(batch) = sequencer.create_batch(true).unwrap() {
+        included.push(batch.transactions().len());
+        sequencer.execute_batch(&batch).expect("Every forced transaction must be due by a batch that has room for it");
+    }
+    assert_eq!(included, vec![10, 2]);
+    assert_eq!(sequencer.forced_transactions_count(), 0);
+}
+
+#[test]
+fn test_mock_source_feeds_forced_queue_once() {
+    let mut sequencer = create_test_sequencer(4);
+    let mut source = MockForcedInclusionSource::new();
+    source.push(ForcedTransaction::new(7, 2, transfer("Carol", 0)));
+    source.push(ForcedTransaction::new(7, 1, transfer("Alice", 0)));
+    let mut tampered = transfer("Alice", 1);
+    tampered.kind = TransactionKind::Transfer(99);
+    source.push(ForcedTransaction::new(8, 0, tampered));
+
+    assert_eq!(sequencer.ingest_forced(&mut source).unwrap(), 2, "Unsigned L1 messages must be skipped");
+    assert_eq!(sequencer.ingest_forced(&mut source).unwrap(), 0);
+    assert_eq!(sequencer.forced_transactions_count(), 2);
+
+    let batch = sequencer.create_batch(true).unwrap().unwrap();
+    assert_eq!(batch.transactions()[0].sender, address("Alice"));
+    assert_eq!(batch.transactions()[1].sender, address("Carol"));
+}
\ No newline at end of file
@@ -1,7 +1,10 @@
+mod common;
+
 use offchain_labs::{Config, OffchainLabs};
 use offchain_labs::config::{ProverConfig, VerifierConfig, SequencerConfig};
-use offchain_labs::sequencer::Transaction;
+use offchain_labs::sequencer::L1Deposit;
 use std::path::PathBuf;
+use common::{signed_transaction, signer};
 
 fn create_test_config() -> Config {
     Config {
@@ -18,8 +21,9 @@ fn create_test_config() -> Config {
             max_pending_transactions: 100,
             max_pending_programs: 50,
             batch_interval_seconds: 10,
-            max_batch_size: 50,
+            max_batch_size: 10,
             max_programs_per_batch: 25,
+            ..Default::default()
         },
     }
 }
@@ -31,11 +35,17 @@ fn test_offchain_labs_initialization() {
     assert!(hvm.is_ok());
 }
 
+fn fund_alice(hvm: &mut OffchainLabs) {
+    hvm.deposit_funds(L1Deposit::new(1, 0, signer("Alice").address(), 100));
+    assert!(hvm.process_pending_batch().unwrap(), "Funding batch was invalid");
+}
+
 #[test]
 fn test_transaction_processing() {
     let config = create_test_config();
     let mut hvm = OffchainLabs::new(config).unwrap();
-    let transaction = Transaction::new("Alice".to_string(), "Bob".to_string(), vec![100], 1, "test_program".to_string());
+    fund_alice(&mut hvm);
+    let transaction = signed_transaction("Alice", "Bob", 100, 0);
     let result = hvm.process_transaction(transaction);
     assert!(result.is_ok());
 }
@@ -44,10 +54,11 @@ fn test_transaction_processing() {
 fn test_multiple_transactions() {
     let config = create_test_config();
     let mut hvm = OffchainLabs::new(config).unwrap();
+    fund_alice(&mut hvm);
     let transactions = vec![
-        Transaction::new("Alice".to_string(), "Bob".to_string(), vec![100], 1, "test_program".to_string()),
-        Transaction::new("Bob".to_string(), "Charlie".to_string(), vec![50], 1, "test_program".to_string()),
-        Transaction::new("Charlie".to_string(), "Alice".to_string(), vec![25], 1, "test_program".to_string()),
+        signed_transaction("Alice", "Bob", 100, 0),
+        signed_transaction("Bob", "Charlie", 50, 0),
+        signed_transaction("Charlie", "Alice", 25, 0),
     ];
 
     for (i, tx) in transactions.into_iter().enumerate() {
@@ -60,8 +71,8 @@ fn test_multiple_transactions() {
 
     let final_state = hvm.get_current_state().unwrap();
     println!("Final state: {:?}", final_state);
-    assert_eq!(final_state.balance(), 768, "Unexpected final balance");
-    assert_eq!(final_state.nonce(), 3, "Unexpected final nonce");
+    assert_eq!(final_state.account_count(), 3, "Expected Alice, Bob and Charlie in final state");
+    assert_eq!(hvm.processed_transactions_count(), 3, "Expected 3 processed transactions");
 
     println!("Processed transactions: {:?}", hvm.get_processed_transactions());
     println!("Pending transactions: {:?}", hvm.get_pending_transactions());
@@ -71,8 +82,9 @@ fn test_multiple_transactions() {
 fn test_zk_snark_proof_generation_and_verification() {
     let config = create_test_config();
     let mut hvm = OffchainLabs::new(config).unwrap();
+    fund_alice(&mut hvm);
     
-    let transaction = Transaction::new("Alice".to_string(), "Bob".to_string(), vec![100], 1, "test_program".to_string());
+    let transaction = signed_transaction("Alice", "Bob", 100, 0);
     let result = hvm.process_transaction(transaction);
     assert!(result.is_ok());
     
@@ -0,0 +1,135 @@
+mod common;
+
+use offchain_labs::config::{DEV_CHAIN_ID, SequencerConfig};
+use offchain_labs::error::HVMError;
+use offchain_labs::sequencer::{Mempool, RejectionReason, Sequencer, Transaction};
+use offchain_labs::zk_rollup::{Account, State};
+use common::{address, signed_transaction, signer};
+
+fn create_test_sequencer(state: State) -> Sequencer {
+    let config = SequencerConfig {
+        max_pending_transactions: 10,
+        max_pending_programs: 3,
+        batch_interval_seconds: 1,
+        max_batch_size: 10,
+        max_programs_per_batch: 2,
+        ..Default::default()
+    };
+    Sequencer::new(state, config)
+}
+
+#[test]
+fn test_gap_is_held_until_filled() {
+    let mut mempool = Mempool::new();
+    mempool.insert(signed_transaction("Alice", "Bob", 1, 1), 0).unwrap();
+    mempool.insert(signed_transaction("Alice", "Bob", 2, 2), 0).unwrap();
+    assert_eq!(mempool.ready_len(), 0);
+    assert_eq!(mempool.future_len(), 2);
+    assert!(mempool.take_ready(10, 0).is_empty());
+
+    mempool.insert(signed_transaction("Alice", "Bob", 0, 0), 0).unwrap();
+    assert_eq!(mempool.ready_len(), 3);
+    assert_eq!(mempool.future_len(), 0);
+
+    let nonces: Vec<u64> = mempool.take_ready(10, 0).iter().map(|tx| tx.nonce).collect();
+    assert_eq!(nonces, vec![0, 1, 2]);
+    assert!(mempool.is_empty());
+}
+
+fn priced(recipient: &str, amount: u128, nonce: u64, max_fee: u128, priority_tip: u128) -> Transaction {
+    let key = signer("Alice");
+    Transaction::transfer(DEV_CHAIN_ID, key.address(), address(recipient), amount, nonce)
+        .with_fee(max_fee, priority_tip)
+        .sign(&key)
+}
+
+#[test]
+fn test_same_nonce_replaces_and_stale_nonce_is_rejected() {
+    let mut mempool = Mempool::new();
+    assert!(mempool.insert(signed_transaction("Alice", "Bob", 1, 3), 3).unwrap().is_none());
+
+    let replaced = mempool.insert(priced("Charlie", 2, 3, 1, 0), 3).unwrap();
+    assert_eq!(replaced.unwrap().recipient, address("Bob"));
+    assert_eq!(mempool.len(), 1);
+    assert_eq!(mempool.transactions()[0].recipient, address("Charlie"));
+
+    match mempool.insert(signed_transaction("Alice", "Bob", 1, 2), 3) {
+        Err(HVMError::Rejected(reason)) => assert_eq!(reason, RejectionReason::NonceTooLow { expected: 3, got: 2 }),
+        other => panic!("Expected a rejection, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_replacement_requires_a_fee_bump() {
+    let mut mempool = Mempool::new();
+    mempool.insert(priced("Bob", 1, 0, 100, 20), 0).unwrap();
+
+    let underpriced = RejectionReason::ReplacementUnderpriced { min_max_fee: 110, min_priority_tip: 22 };
+    for (max_fee, priority_tip) in [(100, 20), (109, 30), (200, 21)] {
+        match mempool.insert(priced("Charlie", 1, 0, max_fee, priority_tip), 0) {
+            Err(HVMError::Rejected(reason)) => assert_eq!(reason, underpriced),
+            other => panic!("Expected an underpriced replacement, got {:?}", other),
+        }
+    }
+    assert_eq!(mempool.transactions()[0].recipient, address("Bob"));
+
+    assert!(mempool.insert(priced("Charlie", 1, 0, 110, 22), 0).unwrap().is_some());
+    assert_eq!(mempool.transactions()[0].recipient, address("Charlie"));
+    assert_eq!(mempool.len(), 1);
+}
+
+#[test]
+fn test_take_ready_interleaves_senders_in_nonce_order() {
+    let mut mempool = Mempool::new();
+    mempool.insert(signed_transaction("Alice", "Bob", 0, 0), 0).unwrap();
+    mempool.insert(signed_transaction("Bob", "Alice", 0, 5), 5).unwrap();
+    mempool.insert(signed_transaction("Alice", "Bob", 0, 1), 0).unwrap();
+    mempool.insert(signed_transaction("Bob", "Alice", 0, 6), 5).unwrap();
+
+    let taken: Vec<(String, u64)> = mempool.take_ready(3, 0).into_iter().map(|tx| (tx.sender, tx.nonce)).collect();
+    assert_eq!(taken, vec![(address("Alice"), 0), (address("Bob"), 5), (address("Alice"), 1)]);
+    assert_eq!(mempool.ready_len(), 1);
+}
+
+#[test]
+fn test_sequencer_batches_only_executable_transactions() {
+    let mut state = State::new();
+    state.set_account(&address("Alice"), Account::new(1_000, 0));
+    let mut sequencer = create_test_sequencer(state);
+
+    sequencer.process_transaction(signed_transaction("Alice", "Bob", 10, 1)).unwrap();
+    assert_eq!(sequencer.future_transactions_count(), 1);
+    assert!(sequencer.create_batch(true).unwrap().is_none());
+
+    sequencer.process_transaction(signed_transaction("Alice", "Bob", 10, 0)).unwrap();
+    let batch = sequencer.create_batch(true).unwrap().unwrap();
+    assert_eq!(batch.transactions().len(), 2);
+    let outcomes = sequencer.execute_batch(&batch).unwrap();
+    assert!(outcomes.iter().all(|outcome| outcome.success));
+    assert_eq!(sequencer.state().get_account(&address("Alice")).unwrap().nonce, 2);
+
+    assert!(sequencer.process_transaction(signed_transaction("Alice", "Bob", 10, 1)).is_err());
+    sequencer.process_transaction(signed_transaction("Alice", "Bob", 10, 2)).unwrap();
+    assert_eq!(sequencer.ready_transactions_count(), 1);
+}
+#[test]
+fn test_committing_a_batch_keeps_nonces_built_on_later_sealed_batches() {
+    let mut state = State::new();
+    state.set_account(&address("Alice"), Account::new(100, 0));
+    let mut sequencer = create_test_sequencer(state);
+    sequencer.process_transaction(signed_transaction("Alice", "Bob", 1, 0)).unwrap();
+    let first = sequencer.create_batch(true).unwrap().unwrap();
+    sequencer.process_transaction(signed_transaction("Alice", "Bob", 1, 1)).unwrap();
+    let second = sequencer.create_batch(true).unwrap().unwrap();
+    sequencer.process_transaction(signed_transaction("Alice", "Bob", 1, 2)).unwrap();
+
+    sequencer.execute_batch(&first).unwrap();
+    assert_eq!(sequencer.ready_transactions_count(), 1, "Nonce 2 follows the sealed nonce 1 and stays ready");
+    assert!(sequencer.process_transaction(signed_transaction("Alice", "Bob", 2, 1)).is_err(), "Nonce 1 is already sealed");
+
+    sequencer.execute_batch(&second).unwrap();
+    let third = sequencer.create_batch(true).unwrap().unwrap();
+    assert_eq!(third.transactions().iter().map(|tx| tx.nonce).collect::<Vec<_>>(), vec![2]);
+    sequencer.execute_batch(&third).unwrap();
+    assert_eq!(sequencer.state().get_account(&address("Alice")).unwrap().nonce, 3);
+}
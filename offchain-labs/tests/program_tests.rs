@@ -0,0 +1,64 @@
+mod common;
+
+use offchain_labs::bend::{BendProgram, ProgramMetadata};
+use offchain_labs::config::DEV_CHAIN_ID;
+use offchain_labs::error::HVMError;
+use offchain_labs::sequencer::{Sequencer, Transaction, TransactionStatus};
+use common::{address, funded_state, seal_and_apply, signer, test_config};
+
+fn program(body: &str) -> BendProgram {
+    let metadata = ProgramMetadata { name: "test".to_string(), version: "1.0".to_string(), description: String::new() };
+    let bytecode = wat::parse_str(format!("(module (memory (export \"memory\") 1) {})", body)).unwrap();
+    BendProgram::new(bytecode, metadata, address("Alice"))
+}
+
+fn execution_error(result: Result<Vec<ark_bn254::Fr>, HVMError>) -> String {
+    match result {
+        Err(HVMError::Execution(message)) => message,
+        other => panic!("Expected an execution error, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_program_output_is_read_from_memory() {
+    let program = program(r#"(func (export "run") (result i32 i32) i32.const 0 i32.const 64)"#);
+    assert_eq!(program.execute(vec![1; 32], 1_000).unwrap().len(), 2);
+}
+
+#[test]
+fn test_malformed_run_results_are_errors() {
+    let no_results = program(r#"(func (export "run"))"#);
+    assert!(execution_error(no_results.execute(Vec::new(), 1_000)).contains("(i32, i32)"));
+
+    let wrong_types = program(r#"(func (export "run") (result i64 i64) i64.const 0 i64.const 0)"#);
+    assert!(execution_error(wrong_types.execute(Vec::new(), 1_000)).contains("(i32, i32)"));
+
+    for (ptr, len) in [(0, -1), (65_500, 64), (-1, 1)] {
+        let out_of_bounds = program(&format!(r#"(func (export "run") (result i32 i32) i32.const {} i32.const {})"#, ptr, len));
+        assert!(execution_error(out_of_bounds.execute(Vec::new(), 1_000)).contains("exceeds"));
+    }
+}
+
+#[test]
+fn test_runaway_program_stops_at_the_gas_limit() {
+    let runaway = program(r#"(func (export "run") (result i32 i32) (loop $spin (br $spin)) i32.const 0 i32.const 0)"#);
+    assert!(execution_error(runaway.execute(Vec::new(), 10_000)).contains("gas limit of 10000"));
+}
+
+#[test]
+fn test_runaway_program_call_fails_its_receipt() {
+    let mut sequencer = Sequencer::new(funded_state(&["Alice"], 100), test_config());
+    let runaway = program(r#"(func (export "run") (result i32 i32) (loop $spin (br $spin)) i32.const 0 i32.const 0)"#);
+    sequencer.deploy_program(runaway.clone()).unwrap();
+
+    let key = signer("Alice");
+    let call = Transaction::call(DEV_CHAIN_ID, key.address(), address("Bob"), runaway.id().to_string(), Vec::new(), 10, 0).sign(&key);
+    sequencer.process_transaction(call.clone()).unwrap();
+    seal_and_apply(&mut sequencer);
+
+    match &sequencer.receipt(&call.hash()).unwrap().status {
+        TransactionStatus::Failed(reason) => assert!(reason.contains("gas limit"), "{}", reason),
+        other => panic!("Expected a failed receipt, got {:?}", other),
+    }
+    assert_eq!(sequencer.state().get_account(&address("Alice")).unwrap().balance, 100);
+}
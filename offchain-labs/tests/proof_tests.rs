@@ -0,0 +1,58 @@
+use offchain_labs::zk_rollup::{Proof, ProofSystem};
+use offchain_labs::zk_rollup::proof::PROOF_FORMAT_VERSION;
+use ark_bn254::Fr;
+
+fn sample_proof() -> Proof {
+    Proof::new(
+        ProofSystem::Groth16Bn254,
+        [7u8; 32],
+        &[Fr::from(1u64), Fr::from(2u64), Fr::from(3u64)],
+        [9u8; 32],
+        vec![0xde, 0xad, 0xbe, 0xef],
+    )
+}
+
+#[test]
+fn test_proof_binary_roundtrip() {
+    let proof = sample_proof();
+    let bytes = proof.to_bytes();
+    assert_eq!(&bytes[..4], b"HVMP");
+    assert_eq!(bytes[4], PROOF_FORMAT_VERSION);
+
+    let decoded = Proof::from_bytes(&bytes).unwrap();
+    assert_eq!(decoded, proof);
+    assert_eq!(decoded.field_inputs(), vec![Fr::from(1u64), Fr::from(2u64), Fr::from(3u64)]);
+}
+
+#[test]
+fn test_proof_json_roundtrip() {
+    let proof = sample_proof();
+    let json = proof.to_json().unwrap();
+    assert!(json.contains("\"system\":\"groth16_bn254\""));
+    assert!(json.contains(&"07".repeat(32)));
+    assert!(json.contains("\"data\":\"deadbeef\""));
+
+    assert_eq!(Proof::from_json(&json).unwrap(), proof);
+}
+
+#[test]
+fn test_proof_rejects_malformed_bytes() {
+    let bytes = sample_proof().to_bytes();
+    assert!(Proof::from_bytes(&bytes[..bytes.len() - 1]).is_err(), "Truncated proof should be rejected");
+
+    let mut trailing = bytes.clone();
+    trailing.push(0);
+    assert!(Proof::from_bytes(&trailing).is_err(), "Trailing bytes should be rejected");
+
+    let mut bad_magic = bytes.clone();
+    bad_magic[0] = b'X';
+    assert!(Proof::from_bytes(&bad_magic).is_err(), "Bad magic should be rejected");
+
+    let mut bad_version = bytes.clone();
+    bad_version[4] = PROOF_FORMAT_VERSION + 1;
+    assert!(Proof::from_bytes(&bad_version).is_err(), "Unknown version should be rejected");
+
+    let mut bad_system = bytes;
+    bad_system[5] = 0xff;
+    assert!(Proof::from_bytes(&bad_system).is_err(), "Unknown proof system should be rejected");
+}
\ No newline at end of file
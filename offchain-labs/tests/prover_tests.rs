@@ -1,8 +1,11 @@
+mod common;
+
 use offchain_labs::{Config, OffchainLabs};
-use offchain_labs::sequencer::Transaction;
+use offchain_labs::sequencer::L1Deposit;
 use offchain_labs::config::{ProverConfig, VerifierConfig, SequencerConfig};
 use offchain_labs::bend::BendProgram;
 use std::path::PathBuf;
+use common::{signed_transaction, signer};
 
 fn create_test_config() -> Config {
     Config {
@@ -19,8 +22,9 @@ fn create_test_config() -> Config {
             max_pending_transactions: 100,
             max_pending_programs: 50,
             batch_interval_seconds: 10,
-            max_batch_size: 50,
+            max_batch_size: 10,
             max_programs_per_batch: 25,
+            ..Default::default()
         },
     }
 }
@@ -30,10 +34,13 @@ async fn test_prover_generate_proof() {
     let config = create_test_config();
     let mut hvm = OffchainLabs::new(config).unwrap();
 
+    hvm.deposit_funds(L1Deposit::new(1, 0, signer("Alice").address(), 100));
+    assert!(hvm.process_pending_batch().unwrap(), "Funding batch was invalid");
+
     let transactions = vec![
-        Transaction::new("Alice".to_string(), "Bob".to_string(), vec![100], 1, "test_program".to_string()),
-        Transaction::new("Bob".to_string(), "Charlie".to_string(), vec![50], 2, "test_program".to_string()),
-        Transaction::new("Charlie".to_string(), "Alice".to_string(), vec![25], 3, "test_program".to_string()),
+        signed_transaction("Alice", "Bob", 100, 0),
+        signed_transaction("Bob", "Charlie", 50, 0),
+        signed_transaction("Charlie", "Alice", 25, 0),
     ];
 
     for (i, tx) in transactions.into_iter().enumerate() {
@@ -44,8 +51,9 @@ async fn test_prover_generate_proof() {
     }
 
     let final_state = hvm.get_current_state().unwrap();
-    assert_eq!(final_state.balance(), 768, "Unexpected final balance");
-    assert_eq!(final_state.nonce(), 3, "Unexpected final nonce");
+    assert_eq!(final_state.account_count(), 3, "Expected Alice, Bob and Charlie in final state");
+    assert_eq!(final_state.get_account(&signer("Charlie").address()).unwrap().balance, 25);
+    assert_eq!(hvm.processed_transactions_count(), 3, "Expected 3 processed transactions");
 }
 
 #[test]
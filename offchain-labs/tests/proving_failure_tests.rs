@@ -0,0 +1,166 @@
+mod common;
+
+use offchain_labs::config::{DEV_CHAIN_ID, ProvingFailurePolicy, SequencerConfig};
+use offchain_labs::sequencer::{ForcedTransaction, L1Deposit, Sequencer, Transaction, TransactionStatus};
+use common::{address, apply, funded_state, signer, test_config};
+
+fn transfer(sender: &str) -> Transaction {
+    let key = signer(sender);
+    Transaction::transfer(DEV_CHAIN_ID, key.address(), address("Bob"), 1, 0).sign(&key)
+}
+
+const SENDERS: [&str; 4] = ["Alice", "Carol", "Dave", "Erin"];
+
+fn create_test_sequencer(policy: ProvingFailurePolicy) -> Sequencer {
+    Sequencer::new(funded_state(&SENDERS, 100), SequencerConfig { proving_failure_policy: policy, ..test_config() })
+}
+
+fn failed(sequencer: &Sequencer, transaction: &Transaction) -> bool {
+    matches!(sequencer.receipt(&transaction.hash()).unwrap().status, TransactionStatus::Failed(_))
+}
+
+#[test]
+fn test_drop_policy_fails_transactions_and_requeues_deposits() {
+    let mut sequencer = create_test_sequencer(ProvingFailurePolicy::Drop);
+    sequencer.submit_deposit(L1Deposit::new(1, 0, address("Bob"), 5));
+    let transactions: Vec<Transaction> = SENDERS[..2].iter().map(|name| transfer(name)).collect();
+    for transaction in &transactions {
+        sequencer.process_transaction(transaction.clone()).unwrap();
+    }
+    let batch = sequencer.create_batch(true).unwrap().unwrap();
+
+    sequencer.handle_proving_failure(&batch.batch_id(), "Prover crashed");
+    assert!(transactions.iter().all(|transaction| failed(&sequencer, transaction)));
+    assert!(matches!(
+        &sequencer.receipt(&transactions[0].hash()).unwrap().status,
+        TransactionStatus::Failed(reason) if reason.contains("Prover crashed")
+    ));
+    assert_eq!(sequencer.pending_deposits_count(), 1, "L1 deposits are never dropped");
+    assert_eq!(sequencer.sealed_state().root(), sequencer.state().root());
+
+    let retry = sequencer.create_batch(true).unwrap().unwrap();
+    assert_eq!(retry.height(), batch.height());
+    assert_eq!(retry.deposits().len(), 1);
+    assert!(retry.transactions().is_empty());
+}
+
+#[test]
+fn test_drop_policy_lets_senders_resubmit_the_dropped_nonce() {
+    let mut sequencer = create_test_sequencer(ProvingFailurePolicy::Drop);
+    let key = signer("Alice");
+    let nonce = |nonce| Transaction::transfer(DEV_CHAIN_ID, key.address(), address("Bob"), 1, nonce).sign(&key);
+    sequencer.process_transaction(nonce(0)).unwrap();
+    let batch = sequencer.create_batch(true).unwrap().unwrap();
+    sequencer.process_transaction(nonce(1)).unwrap();
+    assert_eq!(sequencer.ready_transactions_count(), 1);
+
+    sequencer.handle_proving_failure(&batch.batch_id(), "Prover crashed");
+    assert!(failed(&sequencer, &nonce(0)));
+    assert_eq!(sequencer.ready_transactions_count(), 0, "Nonce 1 waits for the dropped nonce 0");
+    assert_eq!(sequencer.future_transactions_count(), 1);
+
+    let resubmitted = Transaction::transfer(DEV_CHAIN_ID, key.address(), address("Bob"), 2, 0).sign(&key);
+    sequencer.process_transaction(resubmitted.clone()).unwrap();
+    assert_eq!(sequencer.ready_transactions_count(), 2);
+
+    let retry = sequencer.create_batch(true).unwrap().unwrap();
+    let hashes: Vec<_> = retry.transactions().iter().map(Transaction::hash).collect();
+    assert_eq!(hashes, vec![resubmitted.hash(), nonce(1).hash()]);
+    apply(&mut sequencer, &retry);
+    assert_eq!(sequencer.state().get_account(&key.address()).unwrap().nonce, 2);
+}
+
+#[test]
+fn test_requeue_policy_bisects_to_the_failing_transaction() {
+    let mut sequencer = create_test_sequencer(ProvingFailurePolicy::Requeue);
+    let transactions: Vec<Transaction> = SENDERS.iter().map(|name| transfer(name)).collect();
+    for transaction in &transactions {
+        sequencer.process_transaction(transaction.clone()).unwrap();
+    }
+    let poison = transactions[2].hash();
+
+    let mut attempts = 0;
+    while let Some(batch) = sequencer.create_batch(true).unwrap() {
+        attempts += 1;
+        if batch.transactions().iter().any(|transaction| transaction.hash() == poison) {
+            sequencer.handle_proving_failure(&batch.batch_id(), "Constraint system unsatisfied");
+        } else {
+            apply(&mut sequencer, &batch);
+        }
+    }
+
+    assert_eq!(attempts, 5);
+    assert!(failed(&sequencer, &transactions[2]));
+    for (index, transaction) in transactions.iter().enumerate().filter(|(index, _)| *index != 2) {
+        assert_eq!(sequencer.receipt(&transaction.hash()).unwrap().status, TransactionStatus::Proven, "transaction {}", index);
+    }
+    assert_eq!(sequencer.processed_transactions_count(), 3);
+}
+
+#[test]
+fn test_batches_sealed_on_top_of_a_failure_are_requeued_whole() {
+    let mut sequencer = create_test_sequencer(ProvingFailurePolicy::Requeue);
+    sequencer.process_transaction(transfer("Alice")).unwrap();
+    sequencer.process_transaction(transfer("Carol")).unwrap();
+    let first = sequencer.create_batch(true).unwrap().unwrap();
+    sequencer.process_transaction(transfer("Dave")).unwrap();
+    let second = sequencer.create_batch(true).unwrap().unwrap();
+    assert!(sequencer.is_in_flight(&second));
+
+    sequencer.handle_proving_failure(&first.batch_id(), "Timeout");
+    assert!(!sequencer.is_in_flight(&first));
+    assert!(!sequencer.is_in_flight(&second), "Results for the stale batch must be ignored");
+    assert_eq!(sequencer.receipt(&transfer("Dave").hash()).unwrap().status, TransactionStatus::Pending);
+
+    let sizes: Vec<usize> = std::iter::from_fn(|| sequencer.create_batch(true).unwrap())
+        .map(|batch| batch.transactions().len())
+        .collect();
+    assert_eq!(sizes, vec![1, 1, 1]);
+}
+
+#[test]
+fn test_single_transaction_failure_is_dropped_under_requeue() {
+    let mut sequencer = create_test_sequencer(ProvingFailurePolicy::Requeue);
+    let transaction = transfer("Alice");
+    sequencer.process_transaction(transaction.clone()).unwrap();
+    let batch = sequencer.create_batch(true).unwrap().unwrap();
+
+    sequencer.handle_proving_failure(&batch.batch_id(), "Invalid witness");
+    assert!(failed(&sequencer, &transaction));
+    assert!(!sequencer.has_pending_work());
+
+    sequencer.handle_proving_failure(&batch.batch_id(), "Invalid witness");
+    assert!(failed(&sequencer, &transaction), "Repeated failures for the same batch are ignored");
+}
+#[test]
+fn test_requeued_group_is_trimmed_to_fit_behind_forced_transactions() {
+    let senders: Vec<String> = (0..14).map(|index| format!("Sender{}", index)).collect();
+    let names: Vec<&str> = senders.iter().map(String::as_str).collect();
+    let config = SequencerConfig { forced_inclusion_window: 1, ..test_config() };
+    let mut sequencer = Sequencer::new(funded_state(&names, 100), config);
+
+    sequencer.process_transaction(transfer(names[0])).unwrap();
+    let first = sequencer.create_batch(true).unwrap().unwrap();
+    let full: Vec<Transaction> = names[1..11].iter().map(|name| transfer(name)).collect();
+    for transaction in &full {
+        sequencer.process_transaction(transaction.clone()).unwrap();
+    }
+    let second = sequencer.create_batch(true).unwrap().unwrap();
+    assert_eq!(second.transactions().len(), 10);
+    sequencer.handle_proving_failure(&first.batch_id(), "Timeout");
+
+    let forced: Vec<Transaction> = names[11..].iter().map(|name| transfer(name)).collect();
+    for (index, transaction) in forced.iter().enumerate() {
+        assert!(sequencer.submit_forced(ForcedTransaction::new(1, index as u32, transaction.clone())));
+    }
+
+    let mut sizes = Vec::new();
+    while let Some(batch) = sequencer.create_batch(true).unwrap() {
+        sizes.push(batch.transactions().len());
+        apply(&mut sequencer, &batch);
+    }
+    assert_eq!(sizes, vec![10, 3]);
+    for transaction in full.iter().chain(&forced) {
+        assert_eq!(sequencer.receipt(&transaction.hash()).unwrap().status, TransactionStatus::Proven);
+    }
+}
@@ -0,0 +1,93 @@
+mod common;
+
+use offchain_labs::config::DEV_CHAIN_ID;
+use offchain_labs::sequencer::{Sequencer, Transaction, TransactionStatus};
+use offchain_labs::zk_rollup::transition::BASE_GAS;
+use common::{address, apply, funded_state, signer, test_config};
+
+fn transfer(sender: &str, amount: u128, nonce: u64, max_fee: u128) -> Transaction {
+    let key = signer(sender);
+    Transaction::transfer(DEV_CHAIN_ID, key.address(), address("Bob"), amount, nonce)
+        .with_fee(max_fee, 0)
+        .sign(&key)
+}
+
+fn create_test_sequencer() -> Sequencer {
+    Sequencer::new(funded_state(&["Alice"], 100), test_config())
+}
+
+#[test]
+fn test_receipt_moves_through_lifecycle() {
+    let mut sequencer = create_test_sequencer();
+    let tx = transfer("Alice", 10, 0, 0);
+    sequencer.process_transaction(tx.clone()).unwrap();
+    assert_eq!(sequencer.receipt(&tx.hash()).unwrap().status, TransactionStatus::Pending);
+
+    let batch = sequencer.create_batch(true).unwrap().unwrap();
+    let receipt = sequencer.receipt(&tx.hash()).unwrap();
+    assert_eq!(receipt.status, TransactionStatus::Batched);
+    assert_eq!(receipt.batch_id, Some(batch.batch_id()));
+    assert!(sequencer.mark_batch_submitted(&batch.batch_id()).is_err(), "An unproven batch cannot be submitted");
+
+    apply(&mut sequencer, &batch);
+    let receipt = sequencer.receipt(&tx.hash()).unwrap();
+    assert_eq!(receipt.status, TransactionStatus::Proven);
+    assert_eq!(receipt.gas_used, BASE_GAS);
+    assert_eq!(receipt.logs.len(), 1);
+    assert!(receipt.logs[0].starts_with("Transfer 10"));
+
+    assert!(sequencer.mark_batch_finalized(&batch.batch_id()).is_err(), "Finalization requires submission first");
+    sequencer.mark_batch_submitted(&batch.batch_id()).unwrap();
+    assert_eq!(sequencer.receipt(&tx.hash()).unwrap().status, TransactionStatus::Submitted);
+    sequencer.mark_batch_finalized(&batch.batch_id()).unwrap();
+    assert_eq!(sequencer.receipt(&tx.hash()).unwrap().status, TransactionStatus::Finalized);
+    assert_eq!(sequencer.batch_receipts(&batch.batch_id()).len(), 1);
+}
+
+#[test]
+fn test_failed_execution_is_recorded_with_reason() {
+    let mut sequencer = create_test_sequencer();
+    let first = transfer("Alice", 60, 0, 0);
+    let second = transfer("Alice", 60, 1, 0);
+    sequencer.process_transaction(first.clone()).unwrap();
+    sequencer.process_transaction(second.clone()).unwrap();
+    let batch = sequencer.create_batch(true).unwrap().unwrap();
+    apply(&mut sequencer, &batch);
+
+    assert_eq!(sequencer.receipt(&first.hash()).unwrap().status, TransactionStatus::Proven);
+    let failed = sequencer.receipt(&second.hash()).unwrap();
+    assert!(matches!(&failed.status, TransactionStatus::Failed(reason) if reason.contains("Insufficient balance")));
+    assert_eq!(failed.batch_id, Some(batch.batch_id()));
+    assert!(failed.output.is_empty());
+
+    sequencer.mark_batch_submitted(&batch.batch_id()).unwrap();
+    assert!(matches!(sequencer.receipt(&second.hash()).unwrap().status, TransactionStatus::Failed(_)));
+}
+
+#[test]
+fn test_replaced_and_abandoned_transactions_fail() {
+    let mut sequencer = create_test_sequencer();
+    let original = transfer("Alice", 10, 0, 1);
+    let replacement = transfer("Alice", 10, 0, 2);
+    sequencer.process_transaction(original.clone()).unwrap();
+    sequencer.process_transaction(replacement.clone()).unwrap();
+    assert!(matches!(&sequencer.receipt(&original.hash()).unwrap().status, TransactionStatus::Failed(reason) if reason.starts_with("Replaced by")));
+
+    sequencer.create_batch(true).unwrap().unwrap();
+    sequencer.abandon_sealed();
+    assert!(matches!(&sequencer.receipt(&replacement.hash()).unwrap().status, TransactionStatus::Failed(reason) if reason.contains("abandoned")));
+}
+
+#[test]
+fn test_reverted_batch_receipts_fail() {
+    let mut sequencer = create_test_sequencer();
+    let tx = transfer("Alice", 10, 0, 0);
+    sequencer.process_transaction(tx.clone()).unwrap();
+    let batch = sequencer.create_batch(true).unwrap().unwrap();
+    apply(&mut sequencer, &batch);
+
+    sequencer.revert_batch(&batch.batch_id()).unwrap();
+    assert!(matches!(&sequencer.receipt(&tx.hash()).unwrap().status, TransactionStatus::Failed(reason) if reason.contains("reverted")));
+    assert!(sequencer.batch_receipts(&batch.batch_id()).is_empty());
+    assert!(sequencer.mark_batch_submitted(&batch.batch_id()).is_err());
+}
\ No newline at end of file
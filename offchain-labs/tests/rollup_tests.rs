@@ -0,0 +1,154 @@
+mod common;
+
+use offchain_labs::{Config, OffchainLabs};
+use offchain_labs::bend::{BendProgram, ProgramMetadata};
+use offchain_labs::error::HVMError;
+use offchain_labs::prover::ResourceUsage;
+use offchain_labs::sequencer::{Batch, L1Deposit, MockDepositSource};
+use offchain_labs::zk_rollup::{transition, verify_account_proof, Proof, ProofSystem, State, TransactionOutcome, ZKRollup};
+use std::collections::HashMap;
+use common::{signed_transaction, signer};
+
+struct MockRollup {
+    accept_proofs: bool,
+    programs: HashMap<String, BendProgram>,
+}
+
+impl MockRollup {
+    fn new(accept_proofs: bool) -> Self {
+        Self { accept_proofs, programs: HashMap::new() }
+    }
+}
+
+impl ZKRollup for MockRollup {
+    fn generate_proof(&self, state: &State, batch: &Batch) -> Result<Proof, HVMError> {
+        Ok(Proof::new(ProofSystem::Groth16Bn254, [0u8; 32], &[], batch.batch_id(), state.root().to_vec()))
+    }
+
+    fn verify_proof(&self, _proof: &Proof) -> Result<bool, HVMError> {
+        Ok(self.accept_proofs)
+    }
+
+    fn apply_proof(&self, state: &mut State, _proof: &Proof, batch: &Batch) -> Result<Vec<TransactionOutcome>, HVMError> {
+        let transition = transition::apply_batch(state, batch, &self.programs)?;
+        *state = transition.post_state;
+        Ok(transition.outcomes)
+    }
+
+    fn register_program(&mut self, program: BendProgram) {
+        self.programs.insert(program.id().to_string(), program);
+    }
+
+    fn unregister_program(&mut self, program_id: &str) {
+        self.programs.remove(program_id);
+    }
+
+    fn estimate_resource_usage(&self, _program: &BendProgram) -> Result<ResourceUsage, HVMError> {
+        Ok(ResourceUsage { cpu_cycles: 1, memory_usage: 1 })
+    }
+}
+
+#[test]
+fn test_mock_rollup_accepts_batch() {
+    let mut hvm = OffchainLabs::with_rollup(Config::default(), MockRollup::new(true)).unwrap();
+    let transaction = signed_transaction("Alice", "Bob", 0, 0);
+
+    assert!(hvm.process_transaction(transaction).unwrap());
+    assert_eq!(hvm.processed_transactions_count(), 1);
+    assert_eq!(hvm.pending_transactions_count(), 0);
+    assert_eq!(hvm.get_current_state().unwrap().get_account(&signer("Alice").address()).unwrap().nonce, 1);
+}
+
+#[test]
+fn test_balances_are_provable() {
+    let mut hvm = OffchainLabs::with_rollup(Config::default(), MockRollup::new(true)).unwrap();
+    let transaction = signed_transaction("Alice", "Bob", 0, 0);
+    hvm.process_transaction(transaction).unwrap();
+
+    let root = hvm.state_root();
+    let alice = hvm.prove_account(&signer("Alice").address());
+    assert!(alice.is_inclusion());
+    assert_eq!(alice.balance(), hvm.get_balance(&signer("Alice").address()));
+    assert!(verify_account_proof(&alice, &root));
+
+    let bob = hvm.prove_account(&signer("Bob").address());
+    assert!(!bob.is_inclusion());
+    assert!(verify_account_proof(&bob, &root));
+}
+
+#[test]
+fn test_deposits_fund_accounts() {
+    let mut hvm = OffchainLabs::with_rollup(Config::default(), MockRollup::new(true)).unwrap();
+    let mut source = MockDepositSource::new();
+    source.push(L1Deposit::new(7, 0, signer("Alice").address(), 500));
+
+    assert_eq!(hvm.ingest_deposits(&mut source).unwrap(), 1);
+    assert!(hvm.process_pending_batch().unwrap());
+    assert_eq!(hvm.get_balance(&signer("Alice").address()), 500);
+
+    let transaction = signed_transaction("Alice", "Bob", 200, 0);
+    assert!(hvm.process_transaction(transaction).unwrap());
+    assert_eq!(hvm.get_balance(&signer("Bob").address()), 200);
+}
+
+#[test]
+fn test_mock_rollup_rejects_batch() {
+    let mut hvm = OffchainLabs::with_rollup(Config::default(), MockRollup::new(false)).unwrap();
+    let transaction = signed_transaction("Alice", "Bob", 0, 0);
+
+    assert!(!hvm.process_transaction(transaction).unwrap());
+    assert_eq!(hvm.processed_transactions_count(), 0);
+    assert_eq!(hvm.get_current_state().unwrap().root(), State::default().root());
+}
+#[test]
+fn test_reverting_a_deployment_unregisters_the_program() {
+    let mut hvm = OffchainLabs::with_rollup(Config::default(), MockRollup::new(true)).unwrap();
+    let metadata = ProgramMetadata { name: "counter".to_string(), version: "1.0".to_string(), description: String::new() };
+    let program = BendProgram::new(vec![0, 97, 115, 109], metadata, "Alice".to_string());
+    hvm.submit_program(program.clone()).unwrap();
+    assert!(hvm.process_pending_batch().unwrap());
+    assert!(hvm.rollup().programs.contains_key(program.id()));
+
+    let batch_id = hvm.deployment_receipt(program.id()).unwrap().batch_id.unwrap();
+    hvm.revert_batch(&batch_id).unwrap();
+    assert!(!hvm.rollup().programs.contains_key(program.id()), "The prover must not keep reverted programs");
+}
+
+#[test]
+fn test_executing_a_program_charges_the_caller() {
+    let mut hvm = OffchainLabs::with_rollup(Config::default(), MockRollup::new(true)).unwrap();
+    let metadata = ProgramMetadata { name: "counter".to_string(), version: "1.0".to_string(), description: String::new() };
+    let program = BendProgram::new(vec![0, 97, 115, 109], metadata, "Alice".to_string());
+    hvm.submit_program(program.clone()).unwrap();
+    hvm.deposit_funds(L1Deposit::new(1, 0, signer("Alice").address(), 100));
+    assert!(hvm.process_pending_batch().unwrap());
+
+    let result = hvm.execute_program(program.id(), Vec::new(), &signer("Alice"), 0);
+    assert!(matches!(result, Err(HVMError::Execution(_))), "The truncated module cannot run, but the call is still paid for");
+    assert_eq!(hvm.get_balance(&signer("Alice").address()), 98, "The estimated two units of work are charged");
+    assert_eq!(hvm.get_balance(&Config::default().sequencer_config.fee_recipient), 2);
+
+    assert!(matches!(
+        hvm.execute_program(program.id(), Vec::new(), &signer("Bob"), 0),
+        Err(HVMError::InsufficientBalance())
+    ));
+}
+
+#[test]
+fn test_program_does_not_run_on_an_unincluded_payment() {
+    let mut hvm = OffchainLabs::with_rollup(Config::default(), MockRollup::new(true)).unwrap();
+    let metadata = ProgramMetadata { name: "counter".to_string(), version: "1.0".to_string(), description: String::new() };
+    let program = BendProgram::new(vec![0, 97, 115, 109], metadata, "Alice".to_string());
+    hvm.submit_program(program.clone()).unwrap();
+    hvm.deposit_funds(L1Deposit::new(1, 0, signer("Alice").address(), 100));
+    assert!(hvm.process_pending_batch().unwrap());
+
+    for nonce in 1..4 {
+        match hvm.execute_program(program.id(), Vec::new(), &signer("Alice"), nonce) {
+            Err(HVMError::Execution(message)) => assert!(message.contains("not included"), "{}", message),
+            other => panic!("A parked payment must not run the program, got {:?}", other),
+        }
+    }
+    assert_eq!(hvm.get_balance(&signer("Alice").address()), 100);
+    assert_eq!(hvm.get_balance(&Config::default().sequencer_config.fee_recipient), 0);
+}
@@ -1,25 +1,41 @@
 #[warn(unused_imports)]
+mod common;
+
+use offchain_labs::config::DEV_CHAIN_ID;
 use offchain_labs::{
     config::SequencerConfig,
-    sequencer::{Sequencer, Transaction},
-    zk_rollup::{State, Proof},
+    error::HVMError,
+    sequencer::{Sequencer, Transaction, TransactionKind, TransactionSigner},
+    zk_rollup::{Account, State},
 };
+use common::{signed_transaction, signer};
 
-fn create_test_sequencer() -> Sequencer {
-    let config = SequencerConfig {
+fn create_test_config() -> SequencerConfig {
+    SequencerConfig {
         max_pending_transactions: 5,
         max_pending_programs: 3,
         batch_interval_seconds: 1,
         max_batch_size: 3,
         max_programs_per_batch: 2,
-    };
-    Sequencer::new(State::default(), config)
+        ..Default::default()
+    }
+}
+
+fn create_test_sequencer() -> Sequencer {
+    let mut state = State::default();
+    let mut funded: Vec<String> = (0..5).map(|i| signer(&format!("Sender{}", i)).address()).collect();
+    funded.push(signer("Alice").address());
+    funded.push(TransactionSigner::ed25519_from_seed(&[7u8; 32]).address());
+    for address in funded {
+        state.set_account(&address, Account::new(1_000, 0));
+    }
+    Sequencer::new(state, create_test_config())
 }
 
 #[test]
 fn test_process_transaction() {
     let mut sequencer = create_test_sequencer();
-    let tx = Transaction::new("Alice".to_string(), "Bob".to_string(), vec![100], 1, "test_program".to_string());
+    let tx = signed_transaction("Alice", "Bob", 100, 0);
     assert!(sequencer.process_transaction(tx).is_ok());
     assert_eq!(sequencer.pending_transactions_count(), 1);
 }
@@ -28,10 +44,10 @@ fn test_process_transaction() {
 fn test_max_pending_transactions() {
     let mut sequencer = create_test_sequencer();
     for i in 0..5 {
-        let tx = Transaction::new(format!("Sender{}", i), format!("Recipient{}", i), vec![100], 1, "test_program".to_string());
+        let tx = signed_transaction(&format!("Sender{}", i), &format!("Recipient{}", i), 100, 1);
         assert!(sequencer.process_transaction(tx).is_ok());
     }
-    let tx = Transaction::new("Alice".to_string(), "Bob".to_string(), vec![100], 1, "test_program".to_string());
+    let tx = signed_transaction("Alice", "Bob", 100, 1);
     assert!(sequencer.process_transaction(tx).is_err());
 }
 
@@ -40,7 +56,7 @@ fn test_create_batch() {
     let mut sequencer = create_test_sequencer();
 
     for i in 0..4 {
-        let tx = Transaction::new(format!("Sender{}", i), format!("Recipient{}", i), vec![100], 1, "test_program".to_string());
+        let tx = signed_transaction(&format!("Sender{}", i), &format!("Recipient{}", i), 100, 0);
         sequencer.process_transaction(tx).unwrap();
     }
 
@@ -63,22 +79,47 @@ fn test_create_batch() {
 }
 
 #[test]
-fn test_apply_proof() {
-    let mut sequencer = create_test_sequencer();
-    let initial_state = sequencer.get_current_state();
+fn test_execute_batch() {
+    let mut initial_state = State::default();
+    for i in 0..3 {
+        initial_state.set_account(&signer(&format!("Sender{}", i)).address(), Account::new(1_000, 0));
+    }
+    let mut sequencer = Sequencer::new(initial_state.clone(), create_test_config());
     
     for i in 0..3 {
-        let tx = Transaction::new(format!("Sender{}", i), format!("Recipient{}", i), vec![100], 1, "test_program".to_string());
+        let tx = signed_transaction(&format!("Sender{}", i), &format!("Recipient{}", i), 100, 0);
         sequencer.process_transaction(tx).unwrap();
     }
     let batch = sequencer.create_batch(true).unwrap().unwrap();
     
-    let proof = Proof::new(vec![1, 2, 3, 4]);
-    
-    assert!(sequencer.apply_proof(proof, &batch).is_ok());
+    let outcomes = sequencer.execute_batch(&batch).unwrap();
+    assert!(outcomes.iter().all(|outcome| outcome.success));
     
     let new_state = sequencer.get_current_state();
-    assert!(new_state.nonce() > initial_state.nonce());
+    assert_ne!(new_state.root(), initial_state.root());
+    assert_eq!(new_state.get_account(&signer("Sender0").address()), Some(&Account::new(900, 1)));
+    assert_eq!(new_state.get_account(&signer("Recipient0").address()), Some(&Account::new(100, 0)));
     assert_eq!(sequencer.processed_transactions_count(), 3);
     assert_eq!(sequencer.pending_transactions_count(), 0);
+}
+
+#[test]
+fn test_rejects_invalid_signatures() {
+    let mut sequencer = create_test_sequencer();
+
+    let unsigned = Transaction::transfer(DEV_CHAIN_ID, signer("Alice").address(), signer("Bob").address(), 100, 0);
+    assert!(matches!(sequencer.process_transaction(unsigned), Err(HVMError::InvalidSignature(_))));
+
+    let mut tampered = signed_transaction("Alice", "Bob", 100, 0);
+    tampered.kind = TransactionKind::Transfer(200);
+    assert!(matches!(sequencer.process_transaction(tampered), Err(HVMError::InvalidSignature(_))));
+
+    let mut impersonated = signed_transaction("Mallory", "Bob", 100, 0);
+    impersonated.sender = signer("Alice").address();
+    assert!(matches!(sequencer.process_transaction(impersonated), Err(HVMError::InvalidSignature(_))));
+
+    let ed25519 = TransactionSigner::ed25519_from_seed(&[7u8; 32]);
+    let transaction = Transaction::transfer(DEV_CHAIN_ID, ed25519.address(), signer("Bob").address(), 100, 0).sign(&ed25519);
+    assert!(sequencer.process_transaction(transaction).is_ok());
+    assert_eq!(sequencer.pending_transactions_count(), 1);
 }
\ No newline at end of file
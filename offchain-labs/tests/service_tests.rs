@@ -0,0 +1,151 @@
+mod common;
+
+use offchain_labs::bend::{BendProgram, ProgramMetadata};
+use offchain_labs::config::{DEV_CHAIN_ID, SequencerConfig};
+use offchain_labs::error::HVMError;
+use offchain_labs::sequencer::{
+    L1Deposit, ProvenBatch, ProvingStage, SealedBatch, Sequencer, SequencerService, Transaction,
+};
+use offchain_labs::zk_rollup::{transition, Proof, ProofSystem, State};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use common::{address, signer};
+
+fn transfer(sender: &str, amount: u8, nonce: u64) -> Transaction {
+    let key = signer(sender);
+    Transaction::transfer(DEV_CHAIN_ID, key.address(), address("Bob"), amount.into(), nonce).sign(&key)
+}
+
+fn create_test_sequencer(max_batch_size: usize, batch_interval_seconds: u64) -> Sequencer {
+    let config = SequencerConfig {
+        max_pending_transactions: 10,
+        max_pending_programs: 3,
+        batch_interval_seconds,
+        max_batch_size,
+        max_programs_per_batch: 2,
+        ..Default::default()
+    };
+    let mut state = State::new();
+    state.set_account(&address("Alice"), offchain_labs::zk_rollup::Account::new(1_000, 0));
+    Sequencer::new(state, config)
+}
+
+fn deployment(nonce: u64) -> (BendProgram, Transaction) {
+    let key = signer("Alice");
+    let metadata = ProgramMetadata { name: "noop".to_string(), version: "1.0".to_string(), description: String::new() };
+    let bytecode = wat::parse_str(r#"(module (memory (export "memory") 1) (func (export "run") (result i32 i32) i32.const 0 i32.const 0))"#).unwrap();
+    let program = BendProgram::new(bytecode, metadata, key.address());
+    let transaction = Transaction::deploy(DEV_CHAIN_ID, key.address(), program.clone(), nonce).sign(&key);
+    (program, transaction)
+}
+
+#[derive(Clone, Default)]
+struct RecordingStage {
+    proven: Arc<Mutex<Vec<u64>>>,
+    registered: Arc<Mutex<Vec<String>>>,
+    fail: bool,
+}
+
+impl ProvingStage for RecordingStage {
+    fn prove(&mut self, sealed: &SealedBatch) -> Result<ProvenBatch, HVMError> {
+        if self.fail {
+            return Err(HVMError::Prover("Proving backend unavailable".to_string()));
+        }
+        let transition = transition::apply_batch(&sealed.pre_state, &sealed.batch, &HashMap::<String, BendProgram>::new())?;
+        self.proven.lock().unwrap().push(sealed.batch.height());
+        Ok(ProvenBatch {
+            proof: Proof::new(ProofSystem::Groth16Bn254, [0u8; 32], &[], sealed.batch.batch_id(), Vec::new()),
+            post_state: transition.post_state,
+            outcomes: transition.outcomes,
+        })
+    }
+
+    fn register_programs(&mut self, programs: Vec<BendProgram>) {
+        self.registered.lock().unwrap().extend(programs.iter().map(|program| program.id().to_string()));
+    }
+}
+
+#[tokio::test]
+async fn test_seals_when_batch_is_full() {
+    let stage = RecordingStage::default();
+    let service = SequencerService::spawn(create_test_sequencer(2, 3600), stage.clone());
+    let handle = service.handle();
+
+    for nonce in 0..4 {
+        handle.submit(transfer("Alice", 10, nonce)).await.unwrap();
+    }
+    let sequencer = service.shutdown().await.unwrap();
+
+    assert_eq!(*stage.proven.lock().unwrap(), vec![0, 1]);
+    assert_eq!(sequencer.processed_transactions_count(), 4);
+    assert_eq!(sequencer.state().get_account(&address("Bob")).unwrap().balance, 40);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_seals_when_interval_elapses() {
+    let stage = RecordingStage::default();
+    let service = SequencerService::spawn(create_test_sequencer(10, 1), stage.clone());
+    let handle = service.handle();
+
+    handle.submit(transfer("Alice", 10, 0)).await.unwrap();
+    assert!(handle.submit_deposit(L1Deposit::new(1, 0, address("Charlie"), 7)).await.unwrap());
+    assert!(stage.proven.lock().unwrap().is_empty());
+    tokio::time::advance(Duration::from_millis(1500)).await;
+    assert_eq!(handle.seal().await.unwrap(), None, "The interval already sealed the pending work");
+
+    let sequencer = service.shutdown().await.unwrap();
+    assert_eq!(*stage.proven.lock().unwrap(), vec![0]);
+    assert_eq!(sequencer.state().get_account(&address("Charlie")).unwrap().balance, 7);
+}
+
+#[tokio::test]
+async fn test_shutdown_flushes_pending_work() {
+    let stage = RecordingStage::default();
+    let service = SequencerService::spawn(create_test_sequencer(10, 3600), stage.clone());
+    let handle = service.handle();
+
+    handle.submit(transfer("Alice", 10, 0)).await.unwrap();
+    assert!(handle.submit(transfer("Alice", 10, 0)).await.is_err(), "Admission errors reach the submitter");
+
+    let sequencer = service.shutdown().await.unwrap();
+    assert_eq!(sequencer.processed_transactions_count(), 1);
+    assert_eq!(sequencer.pending_transactions_count(), 0);
+    assert!(handle.submit(transfer("Alice", 10, 1)).await.is_err(), "Handles fail once the service stops");
+}
+
+#[tokio::test]
+async fn test_failed_proofs_do_not_commit() {
+    let stage = RecordingStage { fail: true, ..RecordingStage::default() };
+    let service = SequencerService::spawn(create_test_sequencer(10, 3600), stage);
+    let handle = service.handle();
+
+    handle.submit(transfer("Alice", 10, 0)).await.unwrap();
+    let batch_id = handle.seal().await.unwrap().expect("a batch is sealed");
+    assert_eq!(handle.seal().await.unwrap(), None);
+
+    let sequencer = service.shutdown().await.unwrap();
+    assert!(sequencer.head().is_none());
+    assert!(sequencer.batch_header(&batch_id).is_none());
+    assert_eq!(sequencer.sealed_state().root(), sequencer.state().root());
+    assert_eq!(sequencer.processed_transactions_count(), 0);
+}
+#[tokio::test]
+async fn test_programs_are_registered_only_after_commit() {
+    let failing = RecordingStage { fail: true, ..RecordingStage::default() };
+    let service = SequencerService::spawn(create_test_sequencer(10, 3600), failing.clone());
+    let (program, transaction) = deployment(0);
+    service.handle().submit(transaction).await.unwrap();
+    service.handle().seal().await.unwrap().expect("a batch is sealed");
+    let sequencer = service.shutdown().await.unwrap();
+    assert!(sequencer.deployed_programs().is_empty());
+    assert!(failing.registered.lock().unwrap().is_empty(), "A batch that never commits must not register its programs");
+
+    let stage = RecordingStage::default();
+    let service = SequencerService::spawn(create_test_sequencer(10, 3600), stage.clone());
+    service.handle().submit(deployment(0).1).await.unwrap();
+    service.handle().seal().await.unwrap().expect("a batch is sealed");
+    let sequencer = service.shutdown().await.unwrap();
+    assert!(sequencer.deployed_programs().contains_key(program.id()));
+    assert_eq!(*stage.registered.lock().unwrap(), vec![program.id().to_string()]);
+}
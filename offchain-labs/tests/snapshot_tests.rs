@@ -0,0 +1,96 @@
+mod common;
+
+use offchain_labs::config::{DEV_CHAIN_ID, SequencerConfig};
+use offchain_labs::sequencer::{Sequencer, Transaction};
+use offchain_labs::zk_rollup::merkle::Hash;
+use offchain_labs::zk_rollup::{Account, State};
+use common::{address, signer};
+
+fn create_test_sequencer(max_retained_snapshots: usize) -> Sequencer {
+    let mut state = State::new();
+    state.set_account(&address("Alice"), Account::new(1_000, 0));
+    let config = SequencerConfig {
+        max_pending_transactions: 10,
+        max_pending_programs: 3,
+        batch_interval_seconds: 1,
+        max_batch_size: 1,
+        max_programs_per_batch: 2,
+        max_retained_snapshots,
+        ..Default::default()
+    };
+    Sequencer::new(state, config)
+}
+
+fn apply_transfer(sequencer: &mut Sequencer, recipient: &str, nonce: u64) -> (Hash, State) {
+    let alice = signer("Alice");
+    let tx = Transaction::transfer(DEV_CHAIN_ID, alice.address(), address(recipient), 100, nonce).sign(&alice);
+    sequencer.process_transaction(tx).unwrap();
+    let batch = sequencer.create_batch(true).unwrap().unwrap();
+    sequencer.execute_batch(&batch).unwrap();
+    (batch.batch_id(), sequencer.get_current_state())
+}
+
+#[test]
+fn test_rollback_to_batch() {
+    let mut sequencer = create_test_sequencer(8);
+    let (first_id, after_first) = apply_transfer(&mut sequencer, "Bob", 0);
+    let (second_id, _) = apply_transfer(&mut sequencer, "Charlie", 1);
+    let (third_id, _) = apply_transfer(&mut sequencer, "Dave", 2);
+
+    let reverted = sequencer.rollback_to_batch(&first_id).unwrap();
+    assert_eq!(reverted, vec![second_id, third_id]);
+    assert_eq!(sequencer.state().root(), after_first.root());
+    assert_eq!(sequencer.state(), &after_first);
+    assert!(sequencer.state().get_account(&address("Charlie")).is_none());
+    assert_eq!(sequencer.processed_transactions_count(), 1);
+    assert_eq!(sequencer.snapshots().batch_ids(), vec![first_id]);
+}
+
+#[test]
+fn test_revert_batch_restores_pre_state() {
+    let mut sequencer = create_test_sequencer(8);
+    let genesis = sequencer.get_current_state();
+    let (first_id, _) = apply_transfer(&mut sequencer, "Bob", 0);
+    apply_transfer(&mut sequencer, "Bob", 1);
+
+    let reverted = sequencer.revert_batch(&first_id).unwrap();
+    assert_eq!(reverted.len(), 2);
+    assert_eq!(sequencer.state(), &genesis);
+    assert_eq!(sequencer.processed_transactions_count(), 0);
+    assert!(sequencer.snapshots().is_empty());
+
+    let (_, replayed) = apply_transfer(&mut sequencer, "Bob", 0);
+    assert_eq!(replayed.get_account(&address("Alice")), Some(&Account::new(900, 1)));
+}
+
+#[test]
+fn test_snapshot_retention() {
+    let mut sequencer = create_test_sequencer(2);
+    let (first_id, _) = apply_transfer(&mut sequencer, "Bob", 0);
+    let (second_id, _) = apply_transfer(&mut sequencer, "Bob", 1);
+    let (third_id, _) = apply_transfer(&mut sequencer, "Bob", 2);
+
+    assert_eq!(sequencer.snapshots().batch_ids(), vec![second_id, third_id]);
+    assert!(sequencer.rollback_to_batch(&first_id).is_err(), "Evicted batches cannot be rolled back to");
+    assert_eq!(sequencer.state().get_account(&address("Bob")), Some(&Account::new(300, 0)));
+
+    sequencer.rollback_to_batch(&second_id).unwrap();
+    assert_eq!(sequencer.state().get_account(&address("Bob")), Some(&Account::new(200, 0)));
+}
+#[test]
+fn test_snapshots_journal_only_touched_accounts() {
+    let template = create_test_sequencer(8);
+    let mut genesis = template.get_current_state();
+    for index in 0..50 {
+        genesis.set_account(&format!("bystander-{}", index), Account::new(1, 0));
+    }
+    let mut sequencer = Sequencer::new(genesis.clone(), template.config().clone());
+
+    let (first_id, _) = apply_transfer(&mut sequencer, "Bob", 0);
+    let (second_id, _) = apply_transfer(&mut sequencer, "Alice", 1);
+    assert_eq!(sequencer.snapshots().get(&first_id).unwrap().touched_accounts(), 2);
+    assert_eq!(sequencer.snapshots().get(&second_id).unwrap().touched_accounts(), 1, "A self transfer touches one account");
+
+    sequencer.revert_batch(&first_id).unwrap();
+    assert_eq!(sequencer.state(), &genesis);
+}
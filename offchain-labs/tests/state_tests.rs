@@ -0,0 +1,74 @@
+use offchain_labs::zk_rollup::{Account, State};
+use offchain_labs::zk_rollup::merkle::default_hashes;
+
+#[test]
+fn test_empty_state_root() {
+    let state = State::new();
+    assert_eq!(state.root(), default_hashes()[0]);
+    assert!(state.get_account("Alice").is_none());
+}
+
+#[test]
+fn test_set_and_get_account() {
+    let mut state = State::new();
+    let empty_root = state.root();
+
+    let root = state.set_account("Alice", Account::new(100, 1));
+    assert_ne!(root, empty_root, "Root should change after inserting an account");
+    assert_eq!(root, state.root());
+    assert_eq!(state.get_account("Alice"), Some(&Account::new(100, 1)));
+
+    state.set_account("Alice", Account::default());
+    assert_eq!(state.root(), empty_root, "Clearing the only account should restore the empty root");
+    assert_eq!(state.account_count(), 0);
+}
+
+#[test]
+fn test_root_independent_of_update_order() {
+    let mut forward = State::new();
+    forward.set_account("Alice", Account::new(100, 0));
+    forward.set_account("Bob", Account::new(50, 2));
+    forward.set_account("Charlie", Account::new(25, 1));
+
+    let mut batched = State::new();
+    let root = batched.update_accounts(vec![
+        ("Charlie".to_string(), Account::new(25, 1)),
+        ("Bob".to_string(), Account::new(50, 2)),
+        ("Alice".to_string(), Account::new(100, 0)),
+    ]);
+
+    assert_eq!(root, forward.root());
+    assert_eq!(batched, forward);
+}
+
+#[test]
+fn test_state_serialization_roundtrip() {
+    let mut state = State::new();
+    state.set_account("Alice", Account::new(100, 1));
+    state.set_account("Bob", Account::new(7, 0));
+
+    let json = serde_json::to_string(&state).unwrap();
+    let restored: State = serde_json::from_str(&json).unwrap();
+    assert_eq!(restored.root(), state.root());
+    assert_eq!(restored.get_account("Bob"), Some(&Account::new(7, 0)));
+}
+
+#[test]
+fn test_state_diff_reaches_target() {
+    let mut source = State::new();
+    source.set_account("Alice", Account::new(100, 1));
+    source.set_account("Bob", Account::new(7, 0));
+
+    let mut target = source.clone();
+    target.set_account("Alice", Account::new(90, 2));
+    target.set_account("Bob", Account::default());
+    target.set_account("Charlie", Account::new(10, 0));
+
+    let diff = source.diff(&target);
+    assert_eq!(diff.len(), 3);
+    assert!(diff.contains(&("Bob".to_string(), Account::default())));
+
+    source.update_accounts(diff);
+    assert_eq!(source.root(), target.root());
+    assert!(target.diff(&source).is_empty());
+}
\ No newline at end of file
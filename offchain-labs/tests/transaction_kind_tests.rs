@@ -0,0 +1,116 @@
+mod common;
+
+use offchain_labs::bend::{BendProgram, ProgramMetadata};
+use offchain_labs::config::DEV_CHAIN_ID;
+use offchain_labs::error::HVMError;
+use offchain_labs::sequencer::{RejectionReason, Sequencer, Transaction, TransactionStatus};
+use offchain_labs::zk_rollup::transition::{intrinsic_gas, BASE_GAS, BYTE_GAS, PROGRAM_CALL_GAS};
+use common::{address, funded_state, seal_and_apply, signer, test_config};
+
+fn module(body: &str) -> BendProgram {
+    let metadata = ProgramMetadata { name: "counter".to_string(), version: "1.0".to_string(), description: String::new() };
+    BendProgram::new(wat::parse_str(format!("(module {})", body)).unwrap(), metadata, address("Alice"))
+}
+
+fn program() -> BendProgram {
+    module(r#"(memory (export "memory") 1) (func (export "run") (result i32 i32) unreachable)"#)
+}
+
+fn create_test_sequencer() -> Sequencer {
+    Sequencer::new(funded_state(&["Alice"], 100), test_config())
+}
+
+fn rejection(result: Result<(), HVMError>) -> RejectionReason {
+    match result {
+        Err(HVMError::Rejected(reason)) => reason,
+        other => panic!("Expected a rejection, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_deploy_transaction_registers_program() {
+    let mut sequencer = create_test_sequencer();
+    let key = signer("Alice");
+    let deployment = Transaction::deploy(DEV_CHAIN_ID, key.address(), program(), 0).sign(&key);
+    sequencer.process_transaction(deployment.clone()).unwrap();
+    let batch = seal_and_apply(&mut sequencer);
+
+    let program_id = program().id().to_string();
+    assert!(sequencer.deployed_programs().contains_key(&program_id));
+    let receipt = sequencer.deployment_receipt(&program_id).unwrap();
+    assert_eq!(receipt.status, TransactionStatus::Proven);
+    assert_eq!(receipt.batch_id, Some(batch.batch_id()));
+    assert_eq!(sequencer.receipt(&deployment.hash()).unwrap().gas_used, BASE_GAS + BYTE_GAS * program().bytecode.len() as u64);
+    assert_eq!(sequencer.state().get_account(&address("Alice")).unwrap().nonce, 1);
+
+    let again = Transaction::deploy(DEV_CHAIN_ID, key.address(), program(), 1).sign(&key);
+    assert_eq!(rejection(sequencer.process_transaction(again)), RejectionReason::ProgramAlreadyDeployed(program_id));
+}
+
+#[test]
+fn test_deployments_without_the_program_interface_are_rejected() {
+    let mut sequencer = create_test_sequencer();
+    let key = signer("Alice");
+    for body in [
+        r#"(func (export "run") (result i32 i32) i32.const 0 i32.const 0)"#,
+        r#"(memory (export "memory") 1)"#,
+        r#"(memory (export "memory") 1) (func (export "run"))"#,
+        r#"(memory (export "memory") 1) (func (export "run") (param i32) (result i32 i32) i32.const 0 i32.const 0)"#,
+    ] {
+        let deployment = Transaction::deploy(DEV_CHAIN_ID, key.address(), module(body), 0).sign(&key);
+        assert!(matches!(rejection(sequencer.process_transaction(deployment)), RejectionReason::Malformed(_)), "{}", body);
+    }
+    assert_eq!(sequencer.pending_transactions_count(), 0);
+}
+
+#[test]
+fn test_program_calls_are_validated_before_admission() {
+    let mut sequencer = create_test_sequencer();
+    let key = signer("Alice");
+    let program_id = program().id().to_string();
+    let early = Transaction::call(DEV_CHAIN_ID, key.address(), address("Bob"), program_id.clone(), vec![1], 5, 0).sign(&key);
+    assert_eq!(rejection(sequencer.process_transaction(early)), RejectionReason::UnknownProgram(program_id.clone()));
+
+    sequencer.process_transaction(Transaction::deploy(DEV_CHAIN_ID, key.address(), program(), 0).sign(&key)).unwrap();
+    seal_and_apply(&mut sequencer);
+
+    let overdrawn = Transaction::call(DEV_CHAIN_ID, key.address(), address("Bob"), program_id.clone(), vec![1], 101, 1).sign(&key);
+    assert_eq!(
+        rejection(sequencer.process_transaction(overdrawn)),
+        RejectionReason::InsufficientBalance { required: 101, available: 100 }
+    );
+    let affordable = Transaction::call(DEV_CHAIN_ID, key.address(), address("Bob"), program_id, vec![1], 100, 1).sign(&key);
+    assert!(sequencer.process_transaction(affordable).is_ok());
+}
+
+#[test]
+fn test_failed_program_call_does_not_move_value() {
+    let mut sequencer = create_test_sequencer();
+    let key = signer("Alice");
+    sequencer.process_transaction(Transaction::deploy(DEV_CHAIN_ID, key.address(), program(), 0).sign(&key)).unwrap();
+    seal_and_apply(&mut sequencer);
+
+    let call = Transaction::call(DEV_CHAIN_ID, key.address(), address("Bob"), program().id().to_string(), vec![1], 30, 1).sign(&key);
+    sequencer.process_transaction(call.clone()).unwrap();
+    seal_and_apply(&mut sequencer);
+
+    assert!(matches!(sequencer.receipt(&call.hash()).unwrap().status, TransactionStatus::Failed(_)));
+    assert_eq!(sequencer.state().get_account(&address("Alice")).unwrap().balance, 100);
+    assert!(sequencer.state().get_account(&address("Bob")).is_none());
+}
+
+#[test]
+fn test_intrinsic_gas_depends_on_kind() {
+    let alice = address("Alice");
+    let bob = address("Bob");
+    assert_eq!(intrinsic_gas(&Transaction::transfer(DEV_CHAIN_ID, alice.clone(), bob.clone(), u128::MAX, 0)), BASE_GAS);
+    assert_eq!(intrinsic_gas(&Transaction::withdrawal(DEV_CHAIN_ID, alice.clone(), "0xabc".to_string(), 10, 0)), BASE_GAS);
+    assert_eq!(
+        intrinsic_gas(&Transaction::call(DEV_CHAIN_ID, alice.clone(), bob, "counter".to_string(), vec![0; 10], 0, 0)),
+        BASE_GAS + PROGRAM_CALL_GAS + BYTE_GAS * 10
+    );
+    assert_eq!(
+        intrinsic_gas(&Transaction::deploy(DEV_CHAIN_ID, alice, program(), 0)),
+        BASE_GAS + BYTE_GAS * program().bytecode.len() as u64
+    );
+}
\ No newline at end of file
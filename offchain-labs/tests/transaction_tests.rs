@@ -0,0 +1,92 @@
+mod common;
+
+use offchain_labs::config::DEV_CHAIN_ID;
+use offchain_labs::bend::{BendProgram, ProgramMetadata};
+use offchain_labs::sequencer::{Transaction, TRANSACTION_FORMAT_VERSION};
+use sha2::{Digest, Sha256};
+use common::signer;
+
+fn signed_call() -> Transaction {
+    let key = signer("Alice");
+    Transaction::call(DEV_CHAIN_ID, key.address(), "Bob".to_string(), "counter".to_string(), vec![7; 70], 9, 3)
+        .with_fee(5, 1)
+        .sign(&key)
+}
+
+#[test]
+fn test_encoding_round_trips() {
+    let key = signer("Alice");
+    let withdrawal = Transaction::withdrawal(DEV_CHAIN_ID, key.address(), "0xabc".to_string(), 40, 1).sign(&signer("Alice"));
+    let unsigned = Transaction::transfer(DEV_CHAIN_ID, "Carol".to_string(), "Dave".to_string(), 0, 0);
+    let metadata = ProgramMetadata {
+        name: "counter".to_string(),
+        version: "1.0".to_string(),
+        description: "Counts calls".to_string(),
+    };
+    let program = BendProgram::new(vec![0, 97, 115, 109], metadata, key.address());
+    let deployment = Transaction::deploy(DEV_CHAIN_ID, key.address(), program, 2).sign(&key);
+
+    for transaction in [signed_call(), withdrawal, unsigned, deployment] {
+        let encoded = transaction.encode();
+        let decoded = Transaction::decode(&encoded).unwrap();
+        assert_eq!(decoded.encode(), encoded);
+        assert_eq!(transaction.encoded_size(), encoded.len());
+        assert_eq!(decoded.hash(), transaction.hash());
+        assert_eq!(decoded.kind, transaction.kind);
+        assert_eq!(decoded.signature, transaction.signature);
+    }
+    assert!(Transaction::decode(&signed_call().encode()).unwrap().verify_signature().is_ok());
+}
+
+#[test]
+fn test_encoding_is_scale_compatible() {
+    let transaction = Transaction::call(42, "ab".to_string(), String::new(), "p".to_string(), vec![7; 70], 9, 3)
+        .with_fee(5, 1);
+    let mut expected = vec![TRANSACTION_FORMAT_VERSION];
+    expected.extend_from_slice(&42u64.to_le_bytes());
+    expected.extend_from_slice(&[2 << 2, b'a', b'b', 0]);
+    expected.extend_from_slice(&3u64.to_le_bytes());
+    expected.extend_from_slice(&[1, 1 << 2, b'p']);
+    expected.extend_from_slice(&[(70 << 2 | 1) as u8, 70 >> 6]);
+    expected.extend_from_slice(&[7; 70]);
+    expected.extend_from_slice(&9u128.to_le_bytes());
+    expected.extend_from_slice(&5u128.to_le_bytes());
+    expected.extend_from_slice(&1u128.to_le_bytes());
+    assert_eq!(transaction.encode_unsigned(), expected);
+
+    expected.push(0);
+    assert_eq!(transaction.encode(), expected);
+}
+
+#[test]
+fn test_hash_identifies_the_unsigned_transaction() {
+    let transaction = signed_call();
+    let resigned = transaction.clone().sign(&signer("Alice"));
+    assert_ne!(resigned.encode(), transaction.encode(), "sr25519 signatures are randomised");
+    assert_eq!(resigned.hash(), transaction.hash());
+    assert_eq!(transaction.hash(), <[u8; 32]>::from(Sha256::digest(transaction.encode_unsigned())));
+
+    let bumped = Transaction { nonce: 4, ..transaction.clone() };
+    assert_ne!(bumped.hash(), transaction.hash());
+}
+
+#[test]
+fn test_decode_rejects_malformed_encodings() {
+    let encoded = signed_call().encode();
+    assert!(Transaction::decode(&encoded[..encoded.len() - 1]).is_err());
+
+    let mut trailing = encoded.clone();
+    trailing.push(0);
+    assert!(Transaction::decode(&trailing).is_err());
+
+    let mut future = encoded.clone();
+    future[0] = TRANSACTION_FORMAT_VERSION + 1;
+    assert!(Transaction::decode(&future).is_err());
+
+    let canonical = Transaction::transfer(DEV_CHAIN_ID, "ab".to_string(), String::new(), 0, 0).encode();
+    let mut non_canonical = canonical[..9].to_vec();
+    non_canonical.extend_from_slice(&[2 << 2 | 1, 0]);
+    non_canonical.extend_from_slice(&canonical[10..]);
+    assert!(Transaction::decode(&canonical).is_ok());
+    assert!(Transaction::decode(&non_canonical).is_err(), "Short lengths must use single-byte compact mode");
+}
\ No newline at end of file
@@ -0,0 +1,64 @@
+use offchain_labs::config::DEV_CHAIN_ID;
+use offchain_labs::sequencer::{Batch, Transaction};
+use offchain_labs::zk_rollup::{transition, Account, State};
+use std::collections::HashMap;
+
+fn funded_state() -> State {
+    let mut state = State::new();
+    state.set_account("Alice", Account::new(1_000, 0));
+    state.set_account("Bob", Account::new(500, 0));
+    state
+}
+
+#[test]
+fn test_apply_batch_transfers() {
+    let pre_state = funded_state();
+    let batch = Batch::new(vec![
+        Transaction::transfer(DEV_CHAIN_ID, "Alice".to_string(), "Bob".to_string(), 300, 0),
+        Transaction::transfer(DEV_CHAIN_ID, "Bob".to_string(), "Charlie".to_string(), 50, 0),
+    ], vec![]).with_chain_id(DEV_CHAIN_ID);
+
+    let transition = transition::apply_batch(&pre_state, &batch, &HashMap::new()).unwrap();
+    assert!(transition.outcomes.iter().all(|outcome| outcome.success));
+
+    let post_state = transition.post_state;
+    assert_eq!(post_state.get_account("Alice"), Some(&Account::new(700, 1)));
+    assert_eq!(post_state.get_account("Bob"), Some(&Account::new(750, 1)));
+    assert_eq!(post_state.get_account("Charlie"), Some(&Account::new(50, 0)));
+    assert_eq!(pre_state.get_account("Alice"), Some(&Account::new(1_000, 0)), "Pre-state must not be mutated");
+}
+
+#[test]
+fn test_apply_batch_reports_failures() {
+    let pre_state = funded_state();
+    let batch = Batch::new(vec![
+        Transaction::transfer(DEV_CHAIN_ID, "Alice".to_string(), "Bob".to_string(), 10, 5),
+        Transaction::transfer(DEV_CHAIN_ID, "Bob".to_string(), "Alice".to_string(), 600, 0),
+        Transaction::call(DEV_CHAIN_ID, "Alice".to_string(), "Bob".to_string(), "missing_program".to_string(), Vec::new(), 10, 0),
+        Transaction::transfer(DEV_CHAIN_ID, "Alice".to_string(), "Bob".to_string(), 10, 0),
+    ], vec![]).with_chain_id(DEV_CHAIN_ID);
+
+    let transition = transition::apply_batch(&pre_state, &batch, &HashMap::new()).unwrap();
+    let successes: Vec<bool> = transition.outcomes.iter().map(|outcome| outcome.success).collect();
+    assert_eq!(successes, vec![false, false, false, true]);
+    assert!(transition.outcomes[0].error.as_ref().unwrap().contains("nonce"));
+    assert!(transition.outcomes[1].error.as_ref().unwrap().contains("balance"));
+    assert!(transition.outcomes[2].error.as_ref().unwrap().contains("Program not found"));
+
+    assert_eq!(transition.post_state.get_account("Alice"), Some(&Account::new(990, 1)));
+    assert_eq!(transition.post_state.get_account("Bob"), Some(&Account::new(510, 0)));
+}
+
+#[test]
+fn test_replay_is_deterministic() {
+    let genesis = funded_state();
+    let batches = vec![
+        Batch::new(vec![Transaction::transfer(DEV_CHAIN_ID, "Alice".to_string(), "Bob".to_string(), 1, 0)], vec![]).with_chain_id(DEV_CHAIN_ID),
+        Batch::new(vec![Transaction::transfer(DEV_CHAIN_ID, "Bob".to_string(), "Alice".to_string(), 2, 0)], vec![]).with_chain_id(DEV_CHAIN_ID),
+    ];
+
+    let first = transition::replay_batches(&genesis, &batches, &HashMap::new()).unwrap();
+    let second = transition::replay_batches(&genesis, &batches, &HashMap::new()).unwrap();
+    assert_eq!(first.root(), second.root());
+    assert_eq!(first.get_account("Alice"), Some(&Account::new(1_001, 1)));
+}
\ No newline at end of file
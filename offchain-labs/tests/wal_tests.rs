@@ -0,0 +1,229 @@
+mod common;
+
+use offchain_labs::bend::{BendProgram, ProgramMetadata};
+use offchain_labs::config::{DEV_CHAIN_ID, SequencerConfig};
+use offchain_labs::sequencer::{ForcedTransaction, L1Deposit, Sequencer, Transaction, WalRecord, WriteAheadLog};
+use offchain_labs::zk_rollup::State;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::PathBuf;
+use common::{address, apply, signer, test_config};
+
+fn transfer(amount: u8, nonce: u64) -> Transaction {
+    let key = signer("Alice");
+    Transaction::transfer(DEV_CHAIN_ID, key.address(), address("Bob"), amount.into(), nonce).sign(&key)
+}
+
+fn wal_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("hvm-wal-{}-{}.log", name, std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+fn create_test_sequencer() -> Sequencer {
+    Sequencer::new(State::new(), SequencerConfig { max_batch_size: 2, ..test_config() })
+}
+
+#[test]
+fn test_torn_last_record_is_truncated() {
+    let path = wal_path("torn");
+    {
+        let (mut wal, records) = WriteAheadLog::open(&path).unwrap();
+        assert!(records.is_empty());
+        wal.append(&WalRecord::Admitted(transfer(1, 0))).unwrap();
+        wal.append(&WalRecord::Committed([1u8; 32])).unwrap();
+    }
+    let intact_len = std::fs::metadata(&path).unwrap().len();
+    let torn = WalRecord::Admitted(transfer(2, 1)).encode().unwrap();
+    OpenOptions::new().append(true).open(&path).unwrap().write_all(&torn[..torn.len() - 3]).unwrap();
+
+    let (mut wal, records) = WriteAheadLog::open(&path).unwrap();
+    assert_eq!(records.len(), 2);
+    assert!(matches!(records[1], WalRecord::Committed(batch_id) if batch_id == [1u8; 32]));
+    assert_eq!(std::fs::metadata(&path).unwrap().len(), intact_len, "The torn tail is cut off");
+
+    wal.append(&WalRecord::Abandoned).unwrap();
+    let (_, records) = WriteAheadLog::open(&path).unwrap();
+    assert!(matches!(records.last(), Some(WalRecord::Abandoned)));
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_corruption_before_the_tail_is_rejected() {
+    let path = wal_path("corrupt");
+    {
+        let (mut wal, _) = WriteAheadLog::open(&path).unwrap();
+        wal.append(&WalRecord::Admitted(transfer(1, 0))).unwrap();
+        wal.append(&WalRecord::Committed([1u8; 32])).unwrap();
+    }
+    let mut bytes = std::fs::read(&path).unwrap();
+    bytes[12] ^= 0xff;
+    std::fs::write(&path, &bytes).unwrap();
+
+    assert!(WriteAheadLog::open(&path).is_err());
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_corrupt_length_before_the_tail_is_rejected() {
+    let path = wal_path("corrupt-length");
+    {
+        let (mut wal, _) = WriteAheadLog::open(&path).unwrap();
+        wal.append(&WalRecord::Admitted(transfer(1, 0))).unwrap();
+        wal.append(&WalRecord::Committed([1u8; 32])).unwrap();
+    }
+    let mut bytes = std::fs::read(&path).unwrap();
+    bytes[3] = 0x7f;
+    std::fs::write(&path, &bytes).unwrap();
+
+    assert!(WriteAheadLog::open(&path).is_err());
+    assert_eq!(std::fs::read(&path).unwrap(), bytes, "Records after a corrupt length must not be truncated away");
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_restart_restores_mempool_and_unproven_batches() {
+    let path = wal_path("restart");
+    let expected_root;
+    {
+        let mut sequencer = create_test_sequencer();
+        assert!(sequencer.open_wal(&path).unwrap().is_empty());
+        sequencer.submit_deposit(L1Deposit::new(1, 0, address("Alice"), 100));
+        let funding = sequencer.create_batch(true).unwrap().unwrap();
+        apply(&mut sequencer, &funding);
+
+        sequencer.process_transaction(transfer(10, 0)).unwrap();
+        sequencer.process_transaction(transfer(20, 1)).unwrap();
+        sequencer.create_batch(true).unwrap().unwrap();
+        sequencer.process_transaction(transfer(30, 2)).unwrap();
+        sequencer.process_transaction(transfer(40, 4)).unwrap();
+        expected_root = sequencer.sealed_state().root();
+    }
+
+    let mut restored = create_test_sequencer();
+    let backlog = restored.open_wal(&path).unwrap();
+    assert_eq!(backlog.len(), 1);
+    assert_eq!(backlog[0].batch.height(), 1);
+    assert_eq!(backlog[0].batch.transactions().len(), 2);
+    assert_eq!(restored.head().map(|header| header.height), Some(0));
+    assert_eq!(restored.state().get_account(&address("Alice")).unwrap().balance, 100);
+    assert_eq!(restored.sealed_state().root(), expected_root);
+    assert_eq!(restored.ready_transactions_count(), 1);
+    assert_eq!(restored.future_transactions_count(), 1);
+    assert!(!restored.submit_deposit(L1Deposit::new(1, 0, address("Alice"), 100)), "Sealed deposits are not ingested twice");
+
+    apply(&mut restored, &backlog[0].batch);
+    assert_eq!(restored.state().get_account(&address("Bob")).unwrap().balance, 30);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_replay_honours_abandoned_and_reverted_batches() {
+    let path = wal_path("reverted");
+    {
+        let mut sequencer = create_test_sequencer();
+        sequencer.open_wal(&path).unwrap();
+        sequencer.submit_deposit(L1Deposit::new(1, 0, address("Alice"), 100));
+        let funding = sequencer.create_batch(true).unwrap().unwrap();
+        apply(&mut sequencer, &funding);
+        sequencer.submit_deposit(L1Deposit::new(2, 0, address("Bob"), 5));
+        let second = sequencer.create_batch(true).unwrap().unwrap();
+        apply(&mut sequencer, &second);
+        sequencer.revert_batch(&second.batch_id()).unwrap();
+
+        sequencer.process_transaction(transfer(10, 0)).unwrap();
+        sequencer.create_batch(true).unwrap().unwrap();
+        sequencer.abandon_sealed();
+    }
+
+    let mut restored = create_test_sequencer();
+    assert!(restored.open_wal(&path).unwrap().is_empty());
+    assert_eq!(restored.head().map(|header| header.height), Some(0));
+    assert_eq!(restored.pending_deposits_count(), 1, "Reverted deposits are queued again");
+    assert_eq!(restored.pending_transactions_count(), 0);
+    assert_eq!(restored.sealed_state().root(), restored.state().root());
+    std::fs::remove_file(&path).unwrap();
+}
+#[test]
+fn test_restart_restores_forced_transactions() {
+    let path = wal_path("forced");
+    let sealed = ForcedTransaction::new(2, 0, transfer(10, 0));
+    let queued = ForcedTransaction::new(3, 0, transfer(20, 1));
+    {
+        let mut sequencer = create_test_sequencer();
+        sequencer.open_wal(&path).unwrap();
+        sequencer.submit_deposit(L1Deposit::new(1, 0, address("Alice"), 100));
+        let funding = sequencer.create_batch(true).unwrap().unwrap();
+        apply(&mut sequencer, &funding);
+
+        assert!(sequencer.submit_forced(sealed.clone()));
+        sequencer.create_batch(true).unwrap().unwrap();
+        assert!(sequencer.submit_forced(queued.clone()));
+    }
+
+    let mut restored = create_test_sequencer();
+    let backlog = restored.open_wal(&path).unwrap();
+    assert_eq!(backlog.len(), 1);
+    assert_eq!(backlog[0].batch.transactions()[0].hash(), sealed.transaction.hash());
+    assert_eq!(restored.forced_transactions_count(), 2);
+    assert!(!restored.submit_forced(queued.clone()), "Logged forced transactions are not ingested twice");
+
+    apply(&mut restored, &backlog[0].batch);
+    assert_eq!(restored.forced_transactions_count(), 1);
+    let next = restored.create_batch(true).unwrap().unwrap();
+    assert_eq!(next.transactions().iter().map(Transaction::hash).collect::<Vec<_>>(), vec![queued.transaction.hash()]);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_rejected_transactions_are_not_logged() {
+    let path = wal_path("rejected");
+    let rejected = transfer(20, 0);
+    {
+        let mut sequencer = create_test_sequencer();
+        sequencer.open_wal(&path).unwrap();
+        sequencer.submit_deposit(L1Deposit::new(1, 0, address("Alice"), 100));
+        let funding = sequencer.create_batch(true).unwrap().unwrap();
+        apply(&mut sequencer, &funding);
+
+        sequencer.process_transaction(transfer(10, 0)).unwrap();
+        sequencer.create_batch(true).unwrap().unwrap();
+        assert!(sequencer.process_transaction(rejected.clone()).is_err(), "Nonce 0 is already sealed");
+        sequencer.abandon_sealed();
+    }
+
+    let mut restored = create_test_sequencer();
+    assert!(restored.open_wal(&path).unwrap().is_empty());
+    assert_eq!(restored.pending_transactions_count(), 0);
+    assert!(restored.receipt(&rejected.hash()).is_none());
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_restart_restores_directly_deployed_programs() {
+    let path = wal_path("deployed");
+    let metadata = ProgramMetadata { name: "counter".to_string(), version: "1.0".to_string(), description: String::new() };
+    let program = BendProgram::new(vec![0, 97, 115, 109], metadata, address("Alice"));
+    let expected_root;
+    {
+        let mut sequencer = create_test_sequencer();
+        sequencer.open_wal(&path).unwrap();
+        sequencer.deploy_program(program.clone()).unwrap();
+        sequencer.submit_deposit(L1Deposit::new(1, 0, address("Alice"), 100));
+        let funding = sequencer.create_batch(true).unwrap().unwrap();
+        apply(&mut sequencer, &funding);
+
+        let key = signer("Alice");
+        let call = Transaction::call(DEV_CHAIN_ID, key.address(), key.address(), program.id().to_string(), vec![1], 0, 0).sign(&key);
+        sequencer.process_transaction(call).unwrap();
+        let batch = sequencer.create_batch(true).unwrap().unwrap();
+        apply(&mut sequencer, &batch);
+        expected_root = sequencer.state().root();
+    }
+
+    let mut restored = create_test_sequencer();
+    assert!(restored.open_wal(&path).unwrap().is_empty());
+    assert!(restored.deployed_programs().contains_key(program.id()));
+    assert_eq!(restored.state().root(), expected_root);
+    std::fs::remove_file(&path).unwrap();
+}
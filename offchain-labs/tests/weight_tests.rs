@@ -0,0 +1,121 @@
+mod common;
+
+use offchain_labs::bend::{BendProgram, ProgramMetadata};
+use offchain_labs::{Config, OffchainLabs};
+use offchain_labs::config::{DEV_CHAIN_ID, SequencerConfig};
+use offchain_labs::error::HVMError;
+use offchain_labs::sequencer::{BatchWeight, L1Deposit, RejectionReason, Sequencer, Transaction};
+use offchain_labs::zk_rollup::circuit;
+use offchain_labs::zk_rollup::{Account, State};
+use std::time::Duration;
+use common::{address, signer};
+
+fn transfer(sender: &str) -> Transaction {
+    let key = signer(sender);
+    Transaction::transfer(DEV_CHAIN_ID, key.address(), address("Bob"), 1, 0).sign(&key)
+}
+
+fn create_test_sequencer(configure: impl FnOnce(&mut SequencerConfig)) -> Sequencer {
+    let mut state = State::new();
+    for name in ["Alice", "Carol", "Dave"] {
+        state.set_account(&address(name), Account::new(100, 0));
+    }
+    let mut config = SequencerConfig {
+        max_pending_transactions: 10,
+        max_pending_programs: 3,
+        batch_interval_seconds: 1,
+        max_batch_size: 10,
+        max_programs_per_batch: 2,
+        proving_constraints_per_second: 1_000_000,
+        ..Default::default()
+    };
+    configure(&mut config);
+    Sequencer::new(state, config)
+}
+
+#[test]
+fn test_byte_limit_splits_batches_and_predicts_post_size() {
+    let limit = BatchWeight::empty(&SequencerConfig::default()).bytes + 2 * transfer("Alice").encoded_size();
+    let mut sequencer = create_test_sequencer(|config| config.max_batch_bytes = limit);
+    for name in ["Alice", "Carol", "Dave"] {
+        sequencer.process_transaction(transfer(name)).unwrap();
+    }
+    assert!(sequencer.is_batch_full(), "Pending bytes exceed the limit before the item count does");
+
+    let first = sequencer.create_batch(true).unwrap().unwrap();
+    assert_eq!(first.transactions().len(), 2);
+    assert_eq!(sequencer.predict_post_size(&first), first.encoded_size());
+    assert!(first.encoded_size() <= limit);
+
+    let second = sequencer.create_batch(true).unwrap().unwrap();
+    assert_eq!(second.transactions().len(), 1);
+}
+
+#[test]
+fn test_proving_time_covers_the_full_circuit() {
+    let mut sequencer = create_test_sequencer(|config| config.max_batch_size = 2);
+    sequencer.submit_deposit(L1Deposit::new(1, 0, address("Eve"), 10));
+    sequencer.process_transaction(transfer("Alice")).unwrap();
+    sequencer.process_transaction(transfer("Carol")).unwrap();
+
+    let batch = sequencer.create_batch(true).unwrap().unwrap();
+    assert_eq!(batch.deposits().len(), 1);
+    assert_eq!(batch.transactions().len(), 1);
+    let weight = BatchWeight::of_batch(&batch);
+    assert_eq!(weight.transfers, 3, "One deposit, one transfer and the fee credit slot");
+    assert!(weight.transfers <= circuit::capacity_for(2));
+    assert_eq!(
+        sequencer.predict_proving_time(),
+        Duration::from_secs_f64(circuit::constraints_for(circuit::capacity_for(2)) as f64 / 1_000_000.0),
+        "Every slot of the circuit is proven, filled or not"
+    );
+    assert_eq!(sequencer.ready_transactions_count(), 1);
+}
+
+#[test]
+fn test_config_rejects_batches_the_prover_cannot_prove() {
+    assert!(Config::default().validate().is_ok());
+
+    let mut config = Config::default();
+    config.sequencer_config.max_batch_size = config.prover_config.max_batch_size + 1;
+    assert!(config.validate().is_err());
+    assert!(OffchainLabs::new(config).is_err());
+
+    let mut config = Config::default();
+    config.sequencer_config.max_batch_bytes = BatchWeight::empty(&config.sequencer_config).bytes - 1;
+    assert!(config.validate().is_err());
+
+    let mut config = Config::default();
+    config.sequencer_config.fee_recipient = String::new();
+    assert!(config.validate().is_err());
+}
+
+#[test]
+fn test_gas_limit_defers_heavy_programs() {
+    let metadata = ProgramMetadata { name: "heavy".to_string(), version: "1.0".to_string(), description: String::new() };
+    let program = BendProgram::new(vec![0u8; 400], metadata, "Alice".to_string());
+    let program_gas = BatchWeight::of_program(&program).gas;
+    let mut sequencer = create_test_sequencer(|config| config.max_batch_gas = program_gas + 20_000);
+    sequencer.process_transaction(transfer("Alice")).unwrap();
+    sequencer.submit_program(program).unwrap();
+
+    let first = sequencer.create_batch(true).unwrap().unwrap();
+    assert_eq!(first.programs().len(), 1);
+    assert!(first.transactions().is_empty(), "The program exhausts the gas budget");
+    let second = sequencer.create_batch(true).unwrap().unwrap();
+    assert_eq!(second.transactions().len(), 1);
+}
+
+#[test]
+fn test_items_exceeding_an_empty_batch_are_rejected() {
+    let mut sequencer = create_test_sequencer(|config| config.max_batch_gas = 20_000);
+    assert!(matches!(
+        sequencer.process_transaction(transfer("Alice")),
+        Err(HVMError::Rejected(RejectionReason::ExceedsBatchLimits))
+    ));
+
+    let mut sequencer = create_test_sequencer(|config| config.max_batch_bytes = 256);
+    let metadata = ProgramMetadata { name: "huge".to_string(), version: "1.0".to_string(), description: String::new() };
+    assert!(sequencer.submit_program(BendProgram::new(vec![0u8; 512], metadata, "Alice".to_string())).is_err());
+    assert_eq!(sequencer.pending_programs_count(), 0);
+}
\ No newline at end of file
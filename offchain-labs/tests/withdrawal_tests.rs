@@ -0,0 +1,110 @@
+mod common;
+
+use offchain_labs::config::{DEV_CHAIN_ID, SequencerConfig};
+use offchain_labs::sequencer::{Batch, Sequencer, Transaction};
+use offchain_labs::zk_rollup::{transition, Account, State};
+use offchain_labs::zk_rollup::withdrawal::{generate_claim, verify_withdrawal_claim, withdrawal_root};
+use std::collections::HashMap;
+use common::{address, signer};
+
+fn funded_state() -> State {
+    let mut state = State::new();
+    state.set_account("Alice", Account::new(1_000, 0));
+    state.set_account("Bob", Account::new(500, 0));
+    state
+}
+
+fn create_test_config() -> SequencerConfig {
+    SequencerConfig {
+        max_pending_transactions: 10,
+        max_pending_programs: 3,
+        batch_interval_seconds: 1,
+        max_batch_size: 10,
+        max_programs_per_batch: 2,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_withdrawal_burns_balance() {
+    let batch = Batch::new(vec![
+        Transaction::withdrawal(DEV_CHAIN_ID, "Alice".to_string(), "0xa11ce".to_string(), 400, 0),
+        Transaction::withdrawal(DEV_CHAIN_ID, "Bob".to_string(), "0xb0b".to_string(), 600, 0),
+        Transaction::transfer(DEV_CHAIN_ID, "Bob".to_string(), "Alice".to_string(), 50, 0),
+    ], vec![]).with_chain_id(DEV_CHAIN_ID);
+
+    let transition = transition::apply_batch(&funded_state(), &batch, &HashMap::new()).unwrap();
+    assert!(transition.outcomes[0].success);
+    assert!(!transition.outcomes[1].success, "Withdrawing more than the balance must fail");
+    assert!(transition.outcomes[2].success);
+
+    assert_eq!(transition.post_state.get_account("Alice"), Some(&Account::new(650, 1)));
+    assert!(transition.post_state.get_account("0xa11ce").is_none(), "Withdrawals must not credit an L2 account");
+    assert_eq!(transition.withdrawals.len(), 1);
+    assert_eq!(transition.withdrawals[0].amount, 400);
+    assert_eq!(transition.withdrawals[0].recipient, "0xa11ce");
+    assert_eq!(transition.withdrawal_root(), withdrawal_root(&transition.withdrawals));
+}
+
+#[test]
+fn test_withdrawal_claims_verify() {
+    let batch = Batch::new(vec![
+        Transaction::withdrawal(DEV_CHAIN_ID, "Alice".to_string(), "0x01".to_string(), 100, 0),
+        Transaction::withdrawal(DEV_CHAIN_ID, "Alice".to_string(), "0x02".to_string(), 200, 1),
+        Transaction::withdrawal(DEV_CHAIN_ID, "Bob".to_string(), "0x03".to_string(), 300, 0),
+    ], vec![]).with_chain_id(DEV_CHAIN_ID);
+    let transition = transition::apply_batch(&funded_state(), &batch, &HashMap::new()).unwrap();
+    let root = transition.withdrawal_root();
+
+    for index in 0..3 {
+        let claim = generate_claim(&transition.withdrawals, index).unwrap();
+        assert_eq!(claim.withdrawal_root, root);
+        assert_eq!(claim.withdrawal.batch_height, batch.height());
+        assert!(verify_withdrawal_claim(&claim, &root), "Claim {} should verify", index);
+    }
+
+    let mut inflated = generate_claim(&transition.withdrawals, 2).unwrap();
+    inflated.withdrawal.amount = 3_000;
+    assert!(!verify_withdrawal_claim(&inflated, &root));
+
+    let mut moved = generate_claim(&transition.withdrawals, 0).unwrap();
+    moved.withdrawal.index = 1;
+    assert!(!verify_withdrawal_claim(&moved, &root));
+
+    let mut forged = transition.withdrawals.clone();
+    forged[0].amount = 1_000_000;
+    let self_consistent = generate_claim(&forged, 0).unwrap();
+    assert!(!verify_withdrawal_claim(&self_consistent, &root), "Claims must be checked against the proven root");
+
+    assert!(generate_claim(&transition.withdrawals, 3).is_err());
+}
+
+#[test]
+fn test_sequencer_tracks_withdrawals() {
+    let alice = signer("Alice");
+    let mut state = State::new();
+    state.set_account(&alice.address(), Account::new(1_000, 0));
+    let mut sequencer = Sequencer::new(state, create_test_config());
+    sequencer.process_transaction(Transaction::withdrawal(DEV_CHAIN_ID, alice.address(), "0xa11ce".to_string(), 250, 0).sign(&alice)).unwrap();
+    let batch = sequencer.create_batch(true).unwrap().unwrap();
+    sequencer.execute_batch(&batch).unwrap();
+
+    assert_eq!(sequencer.withdrawals(&batch.batch_id()).len(), 1);
+    let claim = sequencer.generate_withdrawal_claim(&batch.batch_id(), 0).unwrap();
+    let proven_root = sequencer.batch_header(&batch.batch_id()).unwrap().withdrawals_root;
+    assert_eq!(claim.withdrawal_root, proven_root);
+    assert!(verify_withdrawal_claim(&claim, &proven_root));
+
+    let json = serde_json::to_string(&claim).unwrap();
+    assert!(verify_withdrawal_claim(&serde_json::from_str(&json).unwrap(), &proven_root));
+
+    let forged = batch.clone().with_withdrawals_root([0u8; 32]);
+    sequencer.revert_batch(&batch.batch_id()).unwrap();
+    assert!(sequencer.execute_batch(&forged).is_err(), "Headers must commit to the batch withdrawals");
+    sequencer.execute_batch(&batch).unwrap();
+
+    sequencer.revert_batch(&batch.batch_id()).unwrap();
+    assert!(sequencer.withdrawals(&batch.batch_id()).is_empty());
+    assert!(sequencer.generate_withdrawal_claim(&batch.batch_id(), 0).is_err());
+    assert_eq!(sequencer.state().get_account(&address("Alice")), Some(&Account::new(1_000, 0)));
+}
\ No newline at end of file
@@ -35,6 +35,7 @@ pub fn process_batch() -> Result<(), Box<dyn std::error::Error>> {
         batch_interval_seconds: 1,
         max_batch_size: 3,
         max_programs_per_batch: 2,
+        ..Default::default()
     };
 
     let mut sequencer = Sequencer::new(State::default(), config);
@@ -59,8 +60,9 @@ pub fn verify_and_submit() -> Result<(), Box<dyn std::error::Error>> {
             max_pending_transactions: 100,
             max_pending_programs: 50,
             batch_interval_seconds: 10,
-            max_batch_size: 50,
+            max_batch_size: 10,
             max_programs_per_batch: 25,
+            ..Default::default()
         },
     };
 